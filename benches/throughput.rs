@@ -0,0 +1,85 @@
+//! Measures how many lines per second PidCat renders, run with `cargo bench`.
+//!
+//! Synthetic `threadtime` lines are piped into the release binary, which reads them
+//! without ADB and writes the colorized output to a plain file and to a discarded stdout.
+
+use std::env;
+use std::fs;
+
+use std::io::Write;
+
+use std::process::Command;
+use std::process::Stdio;
+
+use std::time::Instant;
+
+const LINE_COUNT: usize = 200_000;
+
+const TAGS: [&str; 6] = [
+    "ActivityManager",
+    "OkHttp",
+    "Choreographer",
+    "MainActivity",
+    "WindowManager",
+    "SQLiteDatabase",
+];
+
+const LEVELS: [char; 6] = ['V', 'D', 'I', 'W', 'E', 'F'];
+
+fn get_log(line_count: usize) -> String {
+    let mut log = String::with_capacity(line_count * 120);
+
+    for index in 0..line_count {
+        let seconds = index / 1000;
+        let tag = TAGS[index % TAGS.len()];
+        let level = LEVELS[index % LEVELS.len()];
+        let pid = 1000 + index % 7;
+
+        log.push_str(&format!(
+            "10-18 12:{:02}:{:02}.{:03} {pid:5} {:5} {level} {tag}: message {index} with some \
+             text to wrap https://example.com/api/v1/items?page={index}\n",
+            seconds / 60 % 60,
+            seconds % 60,
+            index % 1000,
+            pid + index % 3,
+        ));
+    }
+
+    log
+}
+
+fn main() {
+    let log = get_log(LINE_COUNT);
+    let output_path = env::temp_dir().join("pidcat_throughput.log");
+
+    let start = Instant::now();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_PidCat"))
+        .args(["-a", "--color", "always", "-o"])
+        .arg(&output_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start PidCat");
+
+    child
+        .stdin
+        .take()
+        .expect("Failed to open PidCat stdin")
+        .write_all(log.as_bytes())
+        .expect("Failed to write log to PidCat");
+
+    let status = child.wait().expect("Failed to wait for PidCat");
+    let elapsed = start.elapsed();
+
+    let _ = fs::remove_file(&output_path);
+
+    assert!(status.success(), "PidCat exited with {status}");
+
+    println!(
+        "throughput: {LINE_COUNT} lines in {:.2?} ({:.0} lines/sec)",
+        elapsed,
+        LINE_COUNT as f64 / elapsed.as_secs_f64()
+    );
+}
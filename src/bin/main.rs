@@ -1,5 +1,8 @@
 #![deny(clippy::unwrap_used)]
 
+use chrono::Local;
+use chrono::NaiveDateTime;
+
 use colored::Color;
 use colored::Colorize;
 
@@ -13,9 +16,32 @@ use pidcat::AdbDevice;
 use pidcat::AdbState;
 use pidcat::AnsiSegment;
 use pidcat::CliArgs;
+use pidcat::ColorAssignment;
+use pidcat::ColorDepth;
+use pidcat::CompressionFormat;
+use pidcat::ExitReason;
+use pidcat::FollowedFile;
+use pidcat::Highlight;
+use pidcat::Layout;
+use pidcat::LayoutColumn;
+use pidcat::LayoutField;
+use pidcat::LayoutItem;
+use pidcat::LineToken;
+use pidcat::LogEntry;
+use pidcat::LogEvent;
 use pidcat::LogLevel;
+use pidcat::LogMerger;
+use pidcat::LogRecord;
 use pidcat::LogSource;
+use pidcat::LogcatFile;
+use pidcat::OutputFormat;
+use pidcat::RecordStyle;
+use pidcat::SESSION_VERSION;
+use pidcat::SessionRecord;
 use pidcat::State;
+use pidcat::SystemTagsProfile;
+use pidcat::Theme;
+use pidcat::TimeFormat;
 use pidcat::ValueOrPanic;
 use pidcat::Writer;
 
@@ -23,34 +49,146 @@ use regex::Regex;
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::env;
 use std::panic::PanicHookInfo;
 
 use std::fs::File;
+use std::fs::read_to_string;
 
 use std::io::BufRead;
 use std::io::BufReader;
+use std::io::Cursor;
 use std::io::Error;
 use std::io::ErrorKind;
 use std::io::Read;
 use std::io::stdin;
+use std::io::stdout;
+
+use std::net::TcpListener;
+use std::net::TcpStream;
 
 use std::panic;
 
+use std::process::Child;
 use std::process::Command;
+use std::process::ExitStatus;
 use std::process::Stdio;
 use std::process::exit;
 use std::process::id;
+use std::sync::Arc;
 use std::sync::Mutex;
 
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::Sender;
+use std::sync::mpsc::SyncSender;
+use std::sync::mpsc::TrySendError;
+use std::sync::mpsc::channel;
+use std::sync::mpsc::sync_channel;
+
+use std::thread;
+use std::thread::JoinHandle;
+
+use std::time::Duration;
+use std::time::Instant;
+
 use strip_ansi_escapes::strip;
 
+use unicode_segmentation::UnicodeSegmentation;
+
+use unicode_width::UnicodeWidthStr;
+
 /// ELLIPSIS is a unicode ellipsis character.
 /// It is used to represent truncated lines.
 static ELLIPSIS: Lazy<&str> = Lazy::new(|| "…");
 
-/// ELLIPSIS_COUNT is the number of characters in [ELLIPSIS]
-/// It is used to represent truncated lines.
-static ELLIPSIS_COUNT: Lazy<usize> = Lazy::new(|| ELLIPSIS.chars().count());
+/// COMMAND_POLL_INTERVAL is how often the command given after `--` is checked for exit.
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// FLUSH_INTERVAL is how long written lines may stay buffered while more lines are pending.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// CONSOLE_RESIZE_POLL_INTERVAL is how often the console width is checked on platforms
+/// without a resize signal.
+#[cfg(not(unix))]
+const CONSOLE_RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// LINE_QUEUE_CAPACITY is how many lines the reader thread may queue for the parser thread,
+/// and the parser thread for the main loop, before waiting for them to be handled.
+const LINE_QUEUE_CAPACITY: usize = 10_000;
+
+/// FALLING_BEHIND_WARNING_INTERVAL is the minimum time between two warnings that the
+/// output cannot keep up with the log source.
+const FALLING_BEHIND_WARNING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// RECONNECT_INTERVAL is how long `--connect` waits before connecting again.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// THREAD_NAMES_RETRY_INTERVAL is the minimum time between two thread name lookups of a
+/// process, threads started after a lookup are found by the next one.
+const THREAD_NAMES_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// COMMAND_EXIT_GRACE_PERIOD is how long logs are still read after the command given
+/// after `--` exits, so its last lines are not lost.
+const COMMAND_EXIT_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// THREAD_NAME_WIDTH is the width of the thread column with --thread-names,
+/// Linux truncates thread names to 15 characters.
+const THREAD_NAME_WIDTH: usize = 15;
+
+/// LEVEL_WIDTH is the width of the level badge, e.g. ` D `.
+const LEVEL_WIDTH: usize = 3;
+
+/// STATUS_TO_STDERR is set when stdout carries machine readable output,
+/// status messages are then printed to stderr instead.
+static STATUS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// STATUS_COLORS is cleared when colors are disabled, status messages are then stripped of
+/// their ANSI codes, which colored still emits while an HTML writer needs them.
+static STATUS_COLORS: AtomicBool = AtomicBool::new(true);
+
+/// CONSOLE_RESIZED is set when the console is resized, the cached console width of the
+/// writers is then read again before the next line.
+static CONSOLE_RESIZED: Lazy<Arc<AtomicBool>> = Lazy::new(|| Arc::new(AtomicBool::new(false)));
+
+/// FALLING_BEHIND is set by the parser thread when the main loop queue is full, i.e. the
+/// lines are rendered or written slower than the log source produces them.
+static FALLING_BEHIND: AtomicBool = AtomicBool::new(false);
+
+/// QUEUED_LINES is the number of lines queued by the parser thread and not yet handled by
+/// the main loop.
+static QUEUED_LINES: AtomicUsize = AtomicUsize::new(0);
+
+/// INTERRUPT_SENDER is set while the main loop reads logs, so that CTRL+C stops it and
+/// the output files are finished properly, e.g. the end of a compressed stream is written.
+static INTERRUPT_SENDER: Mutex<Option<SyncSender<LogEvent>>> = Mutex::new(None);
+
+/// INTERRUPTED is set by CTRL+C, checked by the main loop before each event since the
+/// [LogEvent::Interrupted] event cannot be queued while the main loop queue is full.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Prints a status message to stdout, or to stderr if [STATUS_TO_STDERR] is set.
+///
+/// Follows the same calling semantics as [std::println!].
+macro_rules! status {
+    ($($arg:tt)+) => {
+        let mut message = format!($($arg)+);
+
+        if !STATUS_COLORS.load(Ordering::Relaxed) {
+            message = String::from_utf8_lossy(&strip(message.as_bytes())).to_string();
+        }
+
+        if STATUS_TO_STDERR.load(Ordering::Relaxed) {
+            eprintln!("{message}");
+        } else {
+            println!("{message}");
+        }
+    };
+}
 
 static BACKTRACE_LINE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^#(.*?)pc\s(.*?)$").unwrap_or_panic("Invalid Regex for BACKTRACE_LINE")
@@ -60,29 +198,23 @@ static NATIVE_TAGS_LINE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r".*nativeGetEnabledTags.*").unwrap_or_panic("Invalid Regex for NATIVE_TAGS_LINE")
 });
 
-static LOG_LINE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"^([A-Z])/(.+?)\( *(\d+)\): (.*?)$").unwrap_or_panic("Invalid Regex for LOG_LINE")
-});
-
 static PID_LINE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^\w+\s+(\w+)\s+\w+\s+\w+\s+\w+\s+\w+\s+\w+\s+\w\s(.*?)$")
         .unwrap_or_panic("Invalid Regex for PID_LINE")
 });
 
 static PID_START: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"^.*: Start proc (\d+):([a-zA-Z0-9._:]+)/[a-z0-9]+ for .*? \{(.*?)\}$")
+    Regex::new(r"^Start proc (\d+):([a-zA-Z0-9._:]+)/[a-z0-9]+ for .*? \{(.*?)\}$")
         .unwrap_or_panic("Invalid Regex for PID_START")
 });
 
 static PID_START_UGID: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(
-        r"^.*: Start proc ([a-zA-Z0-9._:]+) for ([a-z]+ [^:]+): pid=(\d+) uid=(\d+) gids=(.*)$",
-    )
-    .unwrap_or_panic("Invalid Regex for PID_START_UGID")
+    Regex::new(r"^Start proc ([a-zA-Z0-9._:]+) for ([a-z]+ [^:]+): pid=(\d+) uid=(\d+) gids=(.*)$")
+        .unwrap_or_panic("Invalid Regex for PID_START_UGID")
 });
 
 static PID_START_DALVIK: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"^E/dalvikvm\(\s*(\d+)\): >>>>> ([a-zA-Z0-9._:]+) \[ userId:0 \| appId:(\d+) \]$")
+    Regex::new(r"^>>>>> ([a-zA-Z0-9._:]+) \[ userId:0 \| appId:(\d+) \]$")
         .unwrap_or_panic("Invalid Regex for PID_START_DALVIK")
 });
 
@@ -97,7 +229,7 @@ static PID_LEAVE: Lazy<Regex> = Lazy::new(|| {
 });
 
 static PID_DEATH: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"^Process ([a-zA-Z0-9._:]+) \(pid (\d+)\) has died.?$")
+    Regex::new(r"^Process ([a-zA-Z0-9._:]+) \(pid (\d+)\) has died.*$")
         .unwrap_or_panic("Invalid Regex for PID_DEATH")
 });
 
@@ -124,92 +256,53 @@ static VISIBLE_PACKAGES: Lazy<Regex> = Lazy::new(|| {
         .unwrap_or_panic("Invalid Regex for VISIBLE_PACKAGES")
 });
 
+/// HIGHLIGHT_COLORS is the palette used by `--highlight` rules without an explicit color,
+/// and for the capture groups of every highlight.
+static HIGHLIGHT_COLORS: [Color; 6] = [
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightYellow,
+    Color::BrightGreen,
+    Color::BrightBlue,
+    Color::BrightRed,
+];
+
 static REGEX_CACHE: Lazy<Mutex<HashMap<String, Option<Regex>>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
-static SYSTEM_TAGS: Lazy<&[&str]> = Lazy::new(|| {
-    &[
-        r"Tile",
-        r"HWUI",
-        r"skia",
-        r"libc",
-        r"libEGL",
-        r"Dialog",
-        r"System",
-        r"OneTrace",
-        r"PreCache",
-        r"PlayCore",
-        r"BpBinder",
-        r"VRI\[.*?\]",
-        r"AudioTrack",
-        r"ImeTracker",
-        r"cutils-dev",
-        r"JavaBinder",
-        r"FrameEvents",
-        r"QualityInfo",
-        r"ViewExtract",
-        r"FirebaseApp",
-        r"AdrenoUtils",
-        r"ViewRootImpl",
-        r"nativeloader",
-        r"WindowManager",
-        r"OverlayHandler",
-        r"ActivityThread",
-        r"SurfaceControl",
-        r"\[UAH_CLIENT\]",
-        r"DisplayManager",
-        r"AdrenoGLES-.*?",
-        r"VelocityTracker",
-        r"OplusBracketLog",
-        r"PipelineWatcher",
-        r"AppWidgetManager",
-        r"BLASTBufferQueue",
-        r"InsetsController",
-        r"FirebaseSessions",
-        r"ProfileInstaller",
-        r"ExtensionsLoader",
-        r"SurfaceSyncGroup",
-        r"DesktopModeFlags",
-        r"AppCompatDelegate",
-        r"AppWidgetProvider",
-        r"AppWidgetHostView",
-        r"ApplicationLoaders",
-        r"OplusGraphicsEvent",
-        r"OplusAppHeapManager",
-        r"FirebaseCrashlytics",
-        r"ViewRootImplExtImpl",
-        r"BufferQueueConsumer",
-        r"BufferQueueProducer",
-        r"OplusCursorFeedback",
-        r"FirebaseInitProvider",
-        r"OplusActivityManager",
-        r"CompatChangeReporter",
-        r"SessionsDependencies",
-        r"OplusInputMethodUtil",
-        r"BufferPoolAccessor.*?",
-        r"OplusViewDebugManager",
-        r"WindowOnBackDispatcher",
-        r"CompactWindowAppManager",
-        r"OplusScrollToTopManager",
-        r"ResourcesManagerExtImpl",
-        r"ScrollOptimizationHelper",
-        r"OplusActivityThreadExtImpl",
-        r"DynamicFramerate\s*\[.*?\]",
-        r"OplusViewDragTouchViewHelper",
-        r"OplusPredictiveBackController",
-        r"OplusSystemUINavigationGesture",
-        r"OplusInputMethodManagerInternal",
-        r"OplusCustomizeRestrictionManager",
-        r"oplus\.android\.OplusFrameworkFactoryImpl",
-    ]
-});
-
 fn get_console_width() -> i16 {
     terminal_size::terminal_size()
         .map(|(terminal_size::Width(width), _)| width as i16)
         .unwrap_or(80)
 }
 
+/// Sets [CONSOLE_RESIZED] on `SIGWINCH`.
+#[cfg(unix)]
+fn watch_console_resize() {
+    signal_hook::flag::register(signal_hook::consts::SIGWINCH, Arc::clone(&CONSOLE_RESIZED))
+        .unwrap_or_panic("Failed to watch console resizes");
+}
+
+/// Sets [CONSOLE_RESIZED] when the console width changes, polled since there is no
+/// resize signal to listen to.
+#[cfg(not(unix))]
+fn watch_console_resize() {
+    thread::spawn(|| {
+        let mut console_width = get_console_width();
+
+        loop {
+            thread::sleep(CONSOLE_RESIZE_POLL_INTERVAL);
+
+            let new_console_width = get_console_width();
+
+            if new_console_width != console_width {
+                console_width = new_console_width;
+                CONSOLE_RESIZED.store(true, Ordering::Relaxed);
+            }
+        }
+    });
+}
+
 fn get_ansi_segments(text: &str) -> Vec<AnsiSegment> {
     let mut segments = Vec::default();
     let mut chars = text.chars().peekable();
@@ -251,7 +344,7 @@ fn get_active_codes_at_pos(segments: &[AnsiSegment], pos: usize) -> Vec<String>
             break;
         }
 
-        if seg.code.contains("0m") {
+        if seg.code == "\x1b[0m" || seg.code == "\x1b[m" {
             active.clear();
         } else {
             active.push(seg.code.clone());
@@ -310,6 +403,7 @@ fn insert_ansi_codes_in_range(
 fn get_wrapped_indent(
     message: &str,
     show_colors: bool,
+    word_wrap: bool,
     width: i16,
     header_width: usize,
     level_foreground: Color,
@@ -331,18 +425,18 @@ fn get_wrapped_indent(
     let plain_message = String::from_utf8_lossy(&plain_message_bytes).to_string();
 
     // Check if wrapping is needed
-    if plain_message.chars().count() <= wrap_width {
+    if plain_message.width() <= wrap_width {
         return message;
     }
 
     let ansi_segments = get_ansi_segments(&message);
     let chars = plain_message.chars().collect::<Vec<_>>();
+    let line_ends = get_line_ends(&plain_message, wrap_width, word_wrap);
 
     let mut current = 0;
     let mut message_buffer = String::default();
 
-    while current < chars.len() {
-        let next_index = std::cmp::min(current + wrap_width, chars.len());
+    for (line_index, &next_index) in line_ends.iter().enumerate() {
         let segment: String = chars[current..next_index].iter().collect();
 
         // Get active codes at the start of this segment (for continuation lines)
@@ -379,8 +473,7 @@ fn get_wrapped_indent(
             };
             message_buffer.push_str(&spaces);
 
-            let future_index = next_index + wrap_width;
-            let is_last_line = future_index >= chars.len();
+            let is_last_line = line_index + 2 == line_ends.len();
             let connector = if level_foreground == level_background {
                 "    "
             } else if !is_last_line {
@@ -411,15 +504,86 @@ fn get_wrapped_indent(
     message_buffer
 }
 
-fn get_token_color(token: &str, state: &mut State) -> Color {
-    if !state.known_tokens.contains_key(token) {
-        if !state.token_colors.is_empty() {
-            let color = state.token_colors[0];
-            state.known_tokens.insert(token.to_string(), color);
-            state.token_colors.rotate_left(0);
-        } else {
-            return Color::White;
+/// Returns the char index where each wrapped line ends, so that every line fits in
+/// `wrap_width` terminal cells without splitting a grapheme.
+///
+/// With `word_wrap`, lines end after the last whitespace or punctuation that fits, and are
+/// only cut at the edge when a single token is wider than the line.
+fn get_line_ends(plain_message: &str, wrap_width: usize, word_wrap: bool) -> Vec<usize> {
+    let mut line_ends = Vec::default();
+    let mut line_width = 0;
+    let mut char_index = 0;
+    let mut previous = "";
+
+    // Char index and line width right after each break opportunity of the current line
+    let mut breaks: Vec<(usize, usize)> = Vec::default();
+
+    for grapheme in plain_message.graphemes(true) {
+        let grapheme_width = grapheme.width();
+
+        // A grapheme wider than the whole line still takes a line of its own
+        while line_width + grapheme_width > wrap_width && line_width > 0 {
+            match breaks.pop() {
+                Some((break_index, break_width)) if word_wrap => {
+                    line_ends.push(break_index);
+                    line_width -= break_width;
+
+                    breaks = breaks
+                        .into_iter()
+                        .filter(|&(index, _)| index > break_index)
+                        .map(|(index, width)| (index, width - break_width))
+                        .collect();
+                }
+                _ => {
+                    line_ends.push(char_index);
+                    line_width = 0;
+                    breaks.clear();
+                }
+            }
+        }
+
+        line_width += grapheme_width;
+        char_index += grapheme.chars().count();
+
+        if is_break_opportunity(grapheme, previous) {
+            breaks.push((char_index, line_width));
         }
+
+        previous = grapheme;
+    }
+
+    line_ends.push(char_index);
+    line_ends
+}
+
+/// Tells whether a line can end right after `grapheme`, i.e. after whitespace or after
+/// punctuation separating the parts of URLs, paths and identifiers.
+///
+/// Decimal and thousands separators between digits are not break opportunities.
+fn is_break_opportunity(grapheme: &str, previous: &str) -> bool {
+    let is_after_digit = previous.chars().all(|char| char.is_ascii_digit()) && !previous.is_empty();
+
+    match grapheme {
+        "." | "," => !is_after_digit,
+        "/" | "\\" | ";" | ":" | "&" | "?" | "=" | "-" | "_" | "|" | ")" | "]" | "}" | ">" => true,
+        _ => grapheme.chars().all(char::is_whitespace),
+    }
+}
+
+fn get_token_color(token: &str, state: &mut State, args: &CliArgs) -> Color {
+    if !state.known_tokens.contains_key(token) {
+        let color = match args.tag_colors {
+            ColorAssignment::Lru => state.token_colors.first().copied(),
+            // Only the hashed color is kept, collisions are resolved when tags are shown
+            ColorAssignment::Hash => {
+                ColorAssignment::get_hashed_color(token, &state.theme.palette, &[])
+            }
+        };
+
+        match color {
+            Some(color) => state.known_tokens.insert(token.to_string(), color),
+            None => return Color::White,
+        };
     }
 
     let color = *state
@@ -427,12 +591,51 @@ fn get_token_color(token: &str, state: &mut State) -> Color {
         .get(token)
         .unwrap_or_panic(&format!("Unknown tag '{}' in known tags", token));
 
-    // Move to end of list (LRU logic)
+    // Move to end of list (LRU logic), known tags may use colors outside the palette
     if let Some(pos) = state.token_colors.iter().position(|&col| col == color) {
         state.token_colors.remove(pos);
-        state.token_colors.rotate_left(0);
+        state.token_colors.push(color);
+    }
+
+    state
+        .visible_tokens
+        .retain(|visible_token| visible_token != token);
+    state.visible_tokens.push(token.to_string());
+
+    if state.visible_tokens.len() > state.theme.palette.len() {
+        state.visible_tokens.remove(0);
+    }
+
+    color
+}
+
+/// Returns the color of a tag, with `--tag-colors hash` a tag sharing its hashed color with
+/// another visible tag is shown in the next free palette color while both are visible, so
+/// tags shown together stay distinguishable without changing their color in later sessions.
+fn get_tag_color(tag: &str, state: &mut State, args: &CliArgs) -> Color {
+    let color = get_token_color(tag, state, args);
+
+    if args.tag_colors != ColorAssignment::Hash || state.theme.tags.contains_key(tag) {
+        return color;
+    }
+
+    state
+        .visible_tags
+        .retain(|(visible_tag, _)| visible_tag != tag);
+
+    let visible_colors = state
+        .visible_tags
+        .iter()
+        .map(|&(_, visible_color)| visible_color)
+        .collect::<Vec<_>>();
+    let color = ColorAssignment::get_hashed_color(tag, &state.theme.palette, &visible_colors)
+        .unwrap_or(color);
+
+    state.visible_tags.push((tag.to_string(), color));
+
+    if state.visible_tags.len() > state.theme.palette.len() {
+        state.visible_tags.remove(0);
     }
-    state.token_colors.push(color);
 
     color
 }
@@ -469,7 +672,7 @@ fn start_adb_server(base_adb_command: &[String]) -> Result<(), Error> {
         .bold();
 
     if !stdout.is_empty() {
-        println!("{stdout}");
+        status!("{stdout}");
     }
 
     Ok(())
@@ -491,7 +694,7 @@ fn get_adb_devices(base_adb_command: &[String]) -> Option<Vec<AdbDevice>> {
                 .map(|line| String::from_utf8_lossy(line).trim().to_string())
                 .filter(|line| !line.is_empty())
                 .map(|device| {
-                    println!("{device}");
+                    status!("{device}");
 
                     let (device_id_str, device_state_str) = re
                         .split(&device)
@@ -546,9 +749,100 @@ fn get_current_app_package(base_adb_command: &[String]) -> Option<Vec<String>> {
     }
 }
 
+fn get_device_manufacturer(base_adb_command: &[String]) -> Option<String> {
+    get_adb_output(
+        base_adb_command,
+        &["shell", "getprop", "ro.product.manufacturer"],
+    )
+}
+
+/// Runs an ADB command and returns its trimmed output, `None` when it fails or prints nothing.
+fn get_adb_output(base_adb_command: &[String], adb_args: &[&str]) -> Option<String> {
+    let mut cmd = Command::new(&base_adb_command[0]);
+    if base_adb_command.len() > 1 {
+        cmd.args(&base_adb_command[1..]);
+    }
+
+    let output = cmd
+        .args(adb_args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .ok()?;
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    if !output.status.success() || value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Returns the first record of a `--record` session file, the device details are only
+/// queried when the logs come from ADB.
+fn get_session_metadata(base_adb_command: &[String], is_live: bool) -> SessionRecord {
+    let get_property = |name: &str| {
+        is_live
+            .then(|| get_adb_output(base_adb_command, &["shell", "getprop", name]))
+            .flatten()
+    };
+
+    SessionRecord::Metadata {
+        version: SESSION_VERSION,
+        recorded_at: Local::now().to_rfc3339(),
+        pidcat_version: env!("CARGO_PKG_VERSION").to_string(),
+        serial: is_live
+            .then(|| get_adb_output(base_adb_command, &["get-serialno"]))
+            .flatten(),
+        manufacturer: get_property("ro.product.manufacturer"),
+        model: get_property("ro.product.model"),
+        android_version: get_property("ro.build.version.release"),
+    }
+}
+
+fn write_session_record(session_writer: &mut Writer, record: &SessionRecord) {
+    let json = serde_json::to_string(record).unwrap_or_panic("Failed to serialize session record");
+
+    session_writer.write(&json);
+    session_writer.write("\n");
+}
+
+fn get_system_tags(base_adb_command: &[String], args: &CliArgs) -> Vec<String> {
+    let profile = match args.system_tags_profile {
+        SystemTagsProfile::Auto => get_device_manufacturer(base_adb_command)
+            .map(|manufacturer| SystemTagsProfile::from_manufacturer(&manufacturer))
+            .unwrap_or(SystemTagsProfile::Aosp),
+        profile => profile,
+    };
+
+    let message = format!("Ignoring system tags using the {profile} profile")
+        .cyan()
+        .bold();
+    status!("{message}");
+
+    let mut system_tags = profile.tags();
+
+    for path in args.system_tags_files.iter().flatten() {
+        let err_msg = format!("Failed to read system tags file {path}");
+        let content = read_to_string(path).unwrap_or_panic(&err_msg);
+
+        system_tags.extend(SystemTagsProfile::parse_tags(&content));
+    }
+
+    system_tags
+}
+
+fn get_theme(args: &CliArgs) -> Theme {
+    let theme_files = args.theme_files.clone().unwrap_or_default();
+    let theme = Theme::load(args.theme, &theme_files).unwrap_or_panic("Failed to load theme");
+
+    theme.downgrade(args.color_depth)
+}
+
 fn get_processes(
     base_adb_command: &[String],
-    catchall_package: &[String],
+    state: &State,
     args: &CliArgs,
 ) -> HashMap<String, String> {
     let mut pids_map = HashMap::default();
@@ -571,7 +865,8 @@ fn get_processes(
                     .get(2)
                     .map_or(String::default(), |mat| mat.as_str().to_string());
 
-                let is_target_package = catchall_package.contains(&process);
+                let is_target_package =
+                    is_matching_package(&process, &state.named_processes, &state.catchall_package);
 
                 if args.all || is_target_package {
                     pids_map.insert(pid, process);
@@ -583,8 +878,57 @@ fn get_processes(
     pids_map
 }
 
-fn get_started_process(line: &str) -> Option<(String, String, String, String, String)> {
-    if let Some(caps) = PID_START.captures(line) {
+fn get_thread_names(base_adb_command: &[String], pid: &str) -> HashMap<String, String> {
+    let mut thread_names = HashMap::default();
+    let mut cmd = Command::new(&base_adb_command[0]);
+
+    if base_adb_command.len() > 1 {
+        cmd.args(&base_adb_command[1..]);
+    }
+
+    let output = cmd
+        .args(["shell", "ps", "-T", "-p", pid])
+        .stdout(Stdio::piped())
+        .output();
+
+    if let Ok(out) = output {
+        let stdout = BufReader::new(&out.stdout[..]);
+        let mut lines = stdout.lines().map_while(Result::ok);
+
+        let Some(header) = lines.next() else {
+            return thread_names;
+        };
+
+        let columns = header.split_whitespace().collect::<Vec<_>>();
+        let tid_index = columns.iter().position(|column| *column == "TID");
+        let name_index = columns
+            .iter()
+            .position(|column| *column == "CMD" || *column == "NAME");
+
+        let (Some(tid_index), Some(name_index)) = (tid_index, name_index) else {
+            return thread_names;
+        };
+
+        for line in lines {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+
+            // Thread names may contain spaces, e.g. `OkHttp Dispatcher`
+            if fields.len() > name_index {
+                thread_names.insert(
+                    fields[tid_index].to_string(),
+                    fields[name_index..].join(" "),
+                );
+            }
+        }
+    }
+
+    thread_names
+}
+
+fn get_started_process(entry: &LogEntry) -> Option<(String, String, String, String, String)> {
+    let message = entry.message.as_str();
+
+    if let Some(caps) = PID_START.captures(message) {
         return Some((
             caps[1].to_string(), // started_pid
             String::default(),   // started_uid
@@ -594,7 +938,7 @@ fn get_started_process(line: &str) -> Option<(String, String, String, String, St
         ));
     }
 
-    if let Some(caps) = PID_START_UGID.captures(line) {
+    if let Some(caps) = PID_START_UGID.captures(message) {
         return Some((
             caps[3].to_string(), // started_pid
             caps[4].to_string(), // started_uid
@@ -604,12 +948,16 @@ fn get_started_process(line: &str) -> Option<(String, String, String, String, St
         ));
     }
 
-    if let Some(caps) = PID_START_DALVIK.captures(line) {
+    // Dalvik announces the process it runs in, from that process
+    if entry.tag == "dalvikvm"
+        && entry.level == LogLevel::ERROR
+        && let Some(caps) = PID_START_DALVIK.captures(message)
+    {
         return Some((
-            caps[1].to_string(), // started_pid
-            caps[3].to_string(), // started_uid
+            entry.pid.clone(),   // started_pid
+            caps[2].to_string(), // started_uid
             String::default(),   // started_gids
-            caps[2].to_string(), // started_package
+            caps[1].to_string(), // started_package
             String::default(),   // started_target
         ));
     }
@@ -661,8 +1009,35 @@ fn get_dead_process(
     None
 }
 
+fn is_matching_glob(pattern: &str, text: &str) -> bool {
+    if !pattern.contains(['*', '?']) {
+        return pattern == text;
+    }
+
+    let regex_pattern = pattern
+        .split_inclusive(['*', '?'])
+        .map(|part| match part.strip_suffix('*') {
+            Some(literal) => format!("{}.*", regex::escape(literal)),
+            None => match part.strip_suffix('?') {
+                Some(literal) => format!("{}.", regex::escape(literal)),
+                None => regex::escape(part),
+            },
+        })
+        .join("");
+    let regex_pattern = format!("^{regex_pattern}$");
+
+    let mut cache = REGEX_CACHE
+        .lock()
+        .unwrap_or_panic("Failed to lock regex cache");
+    let re_opt = cache
+        .entry(regex_pattern.clone())
+        .or_insert_with(|| Regex::new(&regex_pattern).ok());
+
+    matches!(re_opt, Some(re) if re.is_match(text))
+}
+
 fn is_matching_package(
-    token: &String,
+    token: &str,
     named_processes: &[String],
     catchall_package: &[String],
 ) -> bool {
@@ -670,14 +1045,26 @@ fn is_matching_package(
         return true;
     }
 
-    if named_processes.contains(token) {
+    if named_processes
+        .iter()
+        .any(|process| is_matching_glob(process, token))
+    {
         return true;
     }
 
-    match token.find(':') {
-        None => catchall_package.contains(token),
-        Some(index) => catchall_package.contains(&token[..index].to_string()),
-    }
+    let package = token.split(':').next().unwrap_or(token);
+
+    catchall_package
+        .iter()
+        .any(|catchall| is_matching_glob(catchall, package))
+}
+
+fn is_ignored_package(token: &str, ignored_packages: &[String]) -> bool {
+    let package = token.split(':').next().unwrap_or(token);
+
+    ignored_packages
+        .iter()
+        .any(|ignored| is_matching_glob(ignored, token) || is_matching_glob(ignored, package))
 }
 
 fn is_matching_tag(tag: &str, tags: &[String]) -> bool {
@@ -714,74 +1101,153 @@ fn is_matching_tag(tag: &str, tags: &[String]) -> bool {
 
 fn write_token(
     token: &str,
-    writers: &mut [Writer],
+    tokens: &mut Vec<LineToken>,
     wrap: bool,
     header_width: usize,
     level_foreground: Color,
     level_background: Color,
 ) -> usize {
-    let local_header = header_width;
-    for writer in writers.iter_mut() {
-        let buffer = if wrap && writer.width != -1 {
-            writer.width = get_console_width();
+    tokens.push(LineToken {
+        text: token.to_string(),
+        wrap,
+        header_width,
+        level_foreground,
+        level_background,
+    });
 
-            get_wrapped_indent(
-                token,
-                writer.show_colors,
-                writer.width,
-                header_width,
-                level_foreground,
-                level_background,
-            )
-        } else {
-            token.to_string()
-        };
+    header_width
+}
 
-        let token = if writer.show_colors {
-            buffer.clone()
+/// Renders the tokens of a line for a writer with the given console `width`, colors and
+/// wrapping mode.
+fn render_tokens(tokens: &[LineToken], width: i16, show_colors: bool, word_wrap: bool) -> String {
+    let mut buffer = String::default();
+
+    for token in tokens {
+        if token.wrap && width != -1 {
+            buffer.push_str(&get_wrapped_indent(
+                &token.text,
+                show_colors,
+                word_wrap,
+                width,
+                token.header_width,
+                token.level_foreground,
+                token.level_background,
+            ));
         } else {
-            let buffer_bytes = buffer.as_bytes();
-            let plain_buffer_bytes = strip(buffer_bytes);
+            buffer.push_str(&token.text);
+        }
+    }
+
+    if show_colors {
+        buffer
+    } else {
+        String::from_utf8_lossy(&strip(buffer.as_bytes())).to_string()
+    }
+}
+
+/// Writes a rendered line to every text writer in one call, writers sharing the same
+/// console width and color settings reuse the same rendering.
+fn write_tokens(tokens: &[LineToken], writers: &mut [Writer]) {
+    if tokens.is_empty() {
+        return;
+    }
+
+    if CONSOLE_RESIZED.swap(false, Ordering::Relaxed) {
+        let console_width = get_console_width();
+
+        for writer in writers.iter_mut().filter(|writer| writer.width != -1) {
+            writer.width = console_width;
+        }
+    }
 
-            String::from_utf8_lossy(&plain_buffer_bytes).to_string()
+    let mut renders: Vec<((i16, bool, bool), String)> = Vec::default();
+
+    for writer in writers
+        .iter_mut()
+        .filter(|writer| writer.format == OutputFormat::Text)
+    {
+        let settings = (writer.width, writer.show_colors, writer.word_wrap);
+        let index = match renders.iter().position(|(other, _)| *other == settings) {
+            Some(index) => index,
+            None => {
+                let (width, show_colors, word_wrap) = settings;
+                renders.push((
+                    settings,
+                    render_tokens(tokens, width, show_colors, word_wrap),
+                ));
+
+                renders.len() - 1
+            }
         };
 
-        writer.write(&token);
+        writer.write(&renders[index].1);
+    }
+}
+
+/// Flushes the writers, called once the pending lines are written rather than after each
+/// line so that busy devices are not slowed down by a write per line.
+fn flush_writers(
+    writers: &mut [Writer],
+    raw_writer: &mut Option<Writer>,
+    session_writer: &mut Option<Writer>,
+) {
+    for writer in writers
+        .iter_mut()
+        .chain(raw_writer.as_mut())
+        .chain(session_writer.as_mut())
+    {
         writer.flush();
     }
+}
 
-    local_header
+fn write_record(record: &LogRecord, style: &RecordStyle, writers: &mut [Writer]) {
+    for writer in writers
+        .iter_mut()
+        .filter(|writer| writer.format != OutputFormat::Text)
+    {
+        writer.write_record(record, style);
+    }
 }
 
 fn write_started_process(
-    line: &str,
+    entry: &LogEntry,
     state: &mut State,
     writers: &mut [Writer],
+    tokens: &mut Vec<LineToken>,
     header_width: usize,
 ) -> bool {
     let spaces = " ".repeat(header_width.saturating_sub(1));
+    let banner_color = state.theme.banner_start;
+    let text_color = state.theme.banner_text;
 
-    if let Some(procs) = get_started_process(line) {
+    if let Some(procs) = get_started_process(entry) {
         let (started_pid, started_uid, started_gids, started_package, started_target) = procs;
 
         let spaces = spaces
-            .color(Color::Green)
-            .on_color(Color::Green)
+            .color(banner_color)
+            .on_color(banner_color)
             .to_string();
 
         let started_process_message = format!(
             " Process {} created for {}\n",
-            &started_package.color(Color::Yellow),
-            &started_target.color(Color::Yellow)
+            &started_package.color(text_color),
+            &started_target.color(text_color)
         );
 
         let pugid_message = format!(
             " PID: {}   UID: {}   GIDs: {}",
-            &started_pid.color(Color::Yellow),
-            &started_uid.color(Color::Yellow),
-            &started_gids.color(Color::Yellow)
+            &started_pid.color(text_color),
+            &started_uid.color(text_color),
+            &started_gids.color(text_color)
         );
 
+        if is_ignored_package(&started_package, &state.ignored_packages) {
+            state.pids_map.insert(started_pid, started_package);
+
+            return true;
+        }
+
         if is_matching_package(
             &started_package,
             &state.named_processes,
@@ -792,85 +1258,113 @@ fn write_started_process(
                 .insert(started_pid.clone(), started_package.clone());
             state.app_pid = Some(started_pid.clone());
 
+            if !started_uid.is_empty() {
+                state
+                    .uids_map
+                    .insert(started_pid.clone(), started_uid.clone());
+            }
+
+            if let Some(started_time) = entry.time {
+                state.start_times.insert(started_pid.clone(), started_time);
+            }
+
+            write_record(
+                &LogRecord::ProcessStart {
+                    timestamp: entry.timestamp.clone(),
+                    pid: started_pid.clone(),
+                    uid: Some(started_uid.clone()).filter(|uid| !uid.is_empty()),
+                    gids: Some(started_gids.clone()).filter(|gids| !gids.is_empty()),
+                    package: started_package.clone(),
+                    target: Some(started_target.clone()).filter(|target| !target.is_empty()),
+                },
+                &RecordStyle {
+                    token_color: text_color,
+                    level_foreground: banner_color,
+                    level_background: banner_color,
+                    message: started_process_message.clone(),
+                },
+                writers,
+            );
+
             write_token(
                 &spaces,
-                writers,
+                tokens,
                 false,
                 header_width,
-                Color::Green,
-                Color::Green,
+                banner_color,
+                banner_color,
             );
 
             write_token(
                 "\n",
-                writers,
+                tokens,
                 false,
                 header_width,
-                Color::Green,
-                Color::Green,
+                banner_color,
+                banner_color,
             );
 
             write_token(
                 &spaces,
-                writers,
+                tokens,
                 false,
                 header_width,
-                Color::Green,
-                Color::Green,
+                banner_color,
+                banner_color,
             );
 
             write_token(
                 &started_process_message,
-                writers,
+                tokens,
                 true,
                 header_width,
-                Color::Green,
-                Color::Green,
+                banner_color,
+                banner_color,
             );
 
             write_token(
                 &spaces,
-                writers,
+                tokens,
                 false,
                 header_width,
-                Color::Green,
-                Color::Green,
+                banner_color,
+                banner_color,
             );
 
             write_token(
                 &pugid_message,
-                writers,
+                tokens,
                 true,
                 header_width,
-                Color::Green,
-                Color::Green,
+                banner_color,
+                banner_color,
             );
 
             write_token(
                 "\n",
-                writers,
+                tokens,
                 false,
                 header_width,
-                Color::Green,
-                Color::Green,
+                banner_color,
+                banner_color,
             );
 
             write_token(
                 &spaces,
-                writers,
+                tokens,
                 false,
                 header_width,
-                Color::Green,
-                Color::Green,
+                banner_color,
+                banner_color,
             );
 
             write_token(
                 "\n",
-                writers,
+                tokens,
                 false,
                 header_width,
-                Color::Green,
-                Color::Green,
+                banner_color,
+                banner_color,
             );
 
             state.last_tag = None;
@@ -883,72 +1377,115 @@ fn write_started_process(
 }
 
 fn write_dead_process(
-    tag: &str,
-    message: &str,
+    entry: &LogEntry,
     state: &mut State,
     writers: &mut [Writer],
+    tokens: &mut Vec<LineToken>,
     header_width: usize,
 ) -> bool {
     let spaces = " ".repeat(header_width.saturating_sub(1));
+    let banner_color = state.theme.banner_death;
+    let text_color = state.theme.banner_text;
 
     if let Some((dead_pid, dead_process_name)) = get_dead_process(
-        tag,
-        message,
+        &entry.tag,
+        &entry.message,
         &state.pids_map.keys().cloned().collect(),
         &state.named_processes,
         &state.catchall_package,
     ) {
-        let spaces = spaces.color(Color::Red).on_color(Color::Red).to_string();
+        let spaces = spaces
+            .color(banner_color)
+            .on_color(banner_color)
+            .to_string();
 
         let dead_process_message = format!(
             " Process {} (PID: {}) ended\n",
-            &dead_process_name.color(Color::Yellow),
-            &dead_pid.color(Color::Yellow)
+            &dead_process_name.color(text_color),
+            &dead_pid.color(text_color)
         );
 
         if state.pids_map.contains_key(&dead_pid) {
             state.pids_map.remove(&dead_pid);
         }
+        state.uids_map.remove(&dead_pid);
+        state.start_times.remove(&dead_pid);
+        state.thread_names.remove(&dead_pid);
+        state.missing_threads.remove(&dead_pid);
+        state.thread_lookup_times.remove(&dead_pid);
+
+        if is_ignored_package(&dead_process_name, &state.ignored_packages) {
+            return true;
+        }
+
+        write_record(
+            &LogRecord::ProcessDeath {
+                timestamp: entry.timestamp.clone(),
+                pid: dead_pid.clone(),
+                package: dead_process_name.clone(),
+            },
+            &RecordStyle {
+                token_color: text_color,
+                level_foreground: banner_color,
+                level_background: banner_color,
+                message: dead_process_message.clone(),
+            },
+            writers,
+        );
 
         write_token(
             &spaces,
-            writers,
+            tokens,
             false,
             header_width,
-            Color::Red,
-            Color::Red,
+            banner_color,
+            banner_color,
         );
 
-        write_token("\n", writers, false, header_width, Color::Red, Color::Red);
+        write_token(
+            "\n",
+            tokens,
+            false,
+            header_width,
+            banner_color,
+            banner_color,
+        );
 
         write_token(
             &spaces,
-            writers,
+            tokens,
             false,
             header_width,
-            Color::Red,
-            Color::Red,
+            banner_color,
+            banner_color,
         );
 
         write_token(
             &dead_process_message,
-            writers,
+            tokens,
             true,
             header_width,
-            Color::Red,
-            Color::Red,
+            banner_color,
+            banner_color,
         );
 
         write_token(
             &spaces,
-            writers,
+            tokens,
             false,
             header_width,
-            Color::Red,
-            Color::Red,
+            banner_color,
+            banner_color,
         );
 
-        write_token("\n", writers, false, header_width, Color::Red, Color::Red);
+        write_token(
+            "\n",
+            tokens,
+            false,
+            header_width,
+            banner_color,
+            banner_color,
+        );
 
         state.last_tag = None;
 
@@ -958,174 +1495,220 @@ fn write_dead_process(
     false
 }
 
-fn write_pid(
-    state: &mut State,
-    args: &CliArgs,
-    writers: &mut [Writer],
-    header_width: &mut usize,
-    owner: &str,
-    level_foreground: Color,
-    level_background: Color,
-) {
-    let pid_width = args.pid_width as usize;
+fn get_column_display(value: &str, color: Color, column: &LayoutColumn, args: &CliArgs) -> String {
+    let display = column.get_fitted(value, get_column_width(column, args), &ELLIPSIS);
+
+    if args.no_color {
+        display
+    } else {
+        display.color(color).to_string()
+    }
+}
 
-    if args.show_pid && !&owner.is_empty() {
-        let mut display_owner = owner.to_string();
-        let pid_color = get_token_color(owner, state);
+fn get_time_display(entry: &LogEntry, state: &mut State, args: &CliArgs) -> String {
+    let time_format = args.time.unwrap_or(TimeFormat::Absolute);
+
+    match entry.time {
+        Some(time) => {
+            let reference = match time_format {
+                TimeFormat::Absolute => None,
+                TimeFormat::Relative => state
+                    .start_times
+                    .get(&entry.pid)
+                    .copied()
+                    .or(state.first_time),
+                TimeFormat::Delta => state.last_shown_time,
+            };
 
-        if display_owner.len() > pid_width {
-            display_owner.truncate(pid_width - *ELLIPSIS_COUNT);
-            display_owner = format!("{}{}", &display_owner, *ELLIPSIS);
+            state.last_shown_time = Some(time);
+            time_format.get_display(time, reference)
         }
+        None => String::new(),
+    }
+}
 
-        let pid_display = format!("{:width$}", display_owner, width = pid_width);
+fn get_thread_display(entry: &LogEntry, state: &mut State, args: &CliArgs) -> String {
+    let tid = entry.tid.clone().unwrap_or_default();
 
-        let pid_display = if args.no_color {
-            pid_display
-        } else {
-            pid_display.color(pid_color).to_string()
-        };
-        *header_width = write_token(
-            &pid_display,
-            writers,
-            false,
-            *header_width,
-            level_foreground,
-            level_background,
-        );
-        *header_width = write_token(
-            " ",
-            writers,
-            false,
-            *header_width,
-            level_foreground,
-            level_background,
-        );
-        *header_width += pid_width + 1;
+    if !args.thread_names || tid.is_empty() {
+        return tid;
+    }
+
+    if let Some(name) = state
+        .thread_names
+        .get(&entry.pid)
+        .and_then(|threads| threads.get(&tid))
+    {
+        return name.clone();
     }
+
+    let Some(ref thread_name_requests) = state.thread_name_requests else {
+        return tid;
+    };
+
+    state
+        .missing_threads
+        .entry(entry.pid.clone())
+        .or_default()
+        .insert(tid.clone());
+
+    // Unknown threads show their id until a lookup finds them, ps runs at most once per
+    // interval for each process however many lines its new threads log
+    let is_lookup_due = state
+        .thread_lookup_times
+        .get(&entry.pid)
+        .is_none_or(|lookup_time| lookup_time.elapsed() >= THREAD_NAMES_RETRY_INTERVAL);
+
+    if is_lookup_due {
+        let _ = thread_name_requests.send(entry.pid.clone());
+        state
+            .thread_lookup_times
+            .insert(entry.pid.clone(), Instant::now());
+    }
+
+    tid
 }
 
-fn write_package_name(
-    owner: &str,
-    args: &CliArgs,
-    state: &mut State,
-    writers: &mut [Writer],
-    header_width: &mut usize,
-    level_foreground: Color,
-    level_background: Color,
-) {
-    let package_width = args.package_width as usize;
-
-    if args.show_package && !&owner.is_empty() {
-        let package_name = state
-            .pids_map
-            .get(owner)
-            .cloned()
-            .unwrap_or(format!("UNKNOWN({owner})"));
-        let mut display_pkg = package_name.clone();
-        let pkg_color = get_token_color(&package_name, state);
-
-        if display_pkg.len() > package_width {
-            display_pkg.truncate(package_width - *ELLIPSIS_COUNT);
-            display_pkg = format!("{}{}", &display_pkg, *ELLIPSIS);
-        }
+fn get_package_name(owner: &str, state: &State) -> String {
+    state
+        .pids_map
+        .get(owner)
+        .cloned()
+        .unwrap_or(format!("UNKNOWN({owner})"))
+}
 
-        let pkg_display = format!("{:width$}", display_pkg, width = package_width);
-        let pkg_display = if args.no_color {
-            pkg_display
-        } else {
-            pkg_display.color(pkg_color).to_string()
-        };
+/// Returns the tag to show, which is empty when it repeats the tag of the previous line.
+fn get_tag_display(tag: &str, state: &mut State, args: &CliArgs) -> String {
+    if Some(tag.to_string()) != state.last_tag || args.always_show_tags {
+        state.last_tag = Some(tag.to_string());
 
-        *header_width = write_token(
-            &pkg_display,
-            writers,
-            false,
-            *header_width,
-            level_foreground,
-            level_background,
-        );
-        *header_width = write_token(
-            " ",
-            writers,
-            false,
-            *header_width,
-            level_foreground,
-            level_background,
-        );
-        *header_width += package_width + 1;
+        tag.to_string()
+    } else {
+        String::default()
     }
 }
 
-fn write_tag(
-    tag: &str,
-    args: &CliArgs,
+fn write_columns(
+    entry: &LogEntry,
     state: &mut State,
-    writers: &mut [Writer],
+    args: &CliArgs,
+    tokens: &mut Vec<LineToken>,
     header_width: &mut usize,
     level_foreground: Color,
     level_background: Color,
 ) {
-    let tag_width = args.tag_width as usize;
+    let layout = args
+        .layout
+        .as_ref()
+        .unwrap_or_panic("Line layout is resolved by CliArgs::parse_args");
+
+    for item in &layout.items {
+        let (display, width) = match item {
+            LayoutItem::Literal(literal) => (literal.clone(), literal.width()),
+            LayoutItem::Column(column) => {
+                let (value, color) = match column.field {
+                    LayoutField::Source => {
+                        let source = entry.source.clone().unwrap_or_default();
+                        let source_color = get_token_color(&source, state, args);
+
+                        (source, source_color)
+                    }
+                    LayoutField::Time => (get_time_display(entry, state, args), state.theme.time),
+                    LayoutField::Pid => {
+                        (entry.pid.clone(), get_token_color(&entry.pid, state, args))
+                    }
+                    LayoutField::Tid => {
+                        let tid = entry.tid.clone().unwrap_or_default();
+                        let tid_color = get_token_color(&tid, state, args);
 
-    if tag_width > 0 {
-        if Some(tag.to_string()) != state.last_tag || args.always_show_tags {
-            state.last_tag = Some(tag.to_string());
+                        (get_thread_display(entry, state, args), tid_color)
+                    }
+                    LayoutField::Package => {
+                        let package_name = get_package_name(&entry.pid, state);
+                        let pkg_color = get_token_color(&package_name, state, args);
 
-            let mut display_tag = tag.to_string();
+                        (package_name, pkg_color)
+                    }
+                    LayoutField::Tag => match get_tag_display(&entry.tag, state, args) {
+                        tag if tag.is_empty() => (tag, Color::White),
+                        tag => (tag, get_tag_color(&entry.tag, state, args)),
+                    },
+                    LayoutField::Level => {
+                        write_log_level(
+                            entry.level,
+                            args,
+                            tokens,
+                            header_width,
+                            level_foreground,
+                            level_background,
+                        );
+
+                        continue;
+                    }
+                    LayoutField::Message => continue,
+                };
 
-            if display_tag.len() > tag_width {
-                display_tag.truncate(tag_width - *ELLIPSIS_COUNT);
-                display_tag = format!("{}{}", &display_tag, *ELLIPSIS);
+                (
+                    get_column_display(&value, color, column, args),
+                    get_column_width(column, args),
+                )
             }
+        };
 
-            let tag_color = get_token_color(tag, state);
-            let tag_display = if args.show_pid || args.show_package {
-                format!("{:>width$}", display_tag, width = tag_width)
-            } else {
-                format!("{:width$}", display_tag, width = tag_width)
-            };
-
-            let tag_display = if args.no_color {
-                tag_display
-            } else {
-                tag_display.color(tag_color).to_string()
-            };
-
-            *header_width = write_token(
-                &tag_display,
-                writers,
-                false,
-                *header_width,
-                level_foreground,
-                level_background,
-            );
-        } else {
-            *header_width = write_token(
-                &" ".repeat(tag_width),
-                writers,
-                false,
-                *header_width,
-                level_foreground,
-                level_background,
-            );
-        }
         *header_width = write_token(
-            " ",
-            writers,
+            &display,
+            tokens,
             false,
             *header_width,
             level_foreground,
             level_background,
         );
-        *header_width += tag_width + 1;
+        *header_width += width;
+    }
+}
+
+/// Returns the width of a layout column, falling back to the width options when the
+/// layout does not set one.
+///
+/// Thread ids share the PID width, thread names get the 15 characters Linux keeps of them.
+fn get_column_width(column: &LayoutColumn, args: &CliArgs) -> usize {
+    match column.field {
+        LayoutField::Level => LEVEL_WIDTH,
+        LayoutField::Message => 0,
+        _ if column.width.is_some() => column.width.unwrap_or_default(),
+        LayoutField::Source => args
+            .get_source_names()
+            .iter()
+            .map(|name| name.width())
+            .max()
+            .unwrap_or_default(),
+        LayoutField::Time => args.time.unwrap_or(TimeFormat::Absolute).width(),
+        LayoutField::Pid => args.pid_width as usize,
+        LayoutField::Tid if args.thread_names => THREAD_NAME_WIDTH,
+        LayoutField::Tid => args.pid_width as usize,
+        LayoutField::Package => args.package_width as usize,
+        LayoutField::Tag => args.tag_width as usize,
     }
 }
 
+/// Returns the width of everything the layout puts before the message, plus one.
+fn get_header_width(layout: &Layout, args: &CliArgs) -> usize {
+    let columns_width = layout
+        .items
+        .iter()
+        .map(|item| match item {
+            LayoutItem::Literal(literal) => literal.width(),
+            LayoutItem::Column(column) => get_column_width(column, args),
+        })
+        .sum::<usize>();
+
+    columns_width + 1
+}
+
 fn write_log_level(
     level: LogLevel,
     args: &CliArgs,
-    writers: &mut [Writer],
+    tokens: &mut Vec<LineToken>,
     header_width: &mut usize,
     level_foreground: Color,
     level_background: Color,
@@ -1141,225 +1724,831 @@ fn write_log_level(
 
     *header_width = write_token(
         &level_str,
-        writers,
+        tokens,
         false,
         *header_width,
         level_foreground,
         level_background,
     );
-    *header_width = write_token(
-        " ",
-        writers,
+    *header_width += LEVEL_WIDTH;
+}
+
+/// Returns the byte ranges of `message` matched by the `--highlight` patterns and their colors.
+fn get_highlight_spans(
+    message: &str,
+    highlights: &[Highlight],
+    color_depth: ColorDepth,
+) -> Vec<(usize, usize, Color)> {
+    let mut spans: Vec<(usize, usize, Color)> = Vec::default();
+
+    for (index, highlight) in highlights.iter().enumerate() {
+        let match_color = highlight
+            .color
+            .map(|color| color_depth.downgrade(color))
+            .unwrap_or(HIGHLIGHT_COLORS[index % HIGHLIGHT_COLORS.len()]);
+
+        for caps in highlight.regex.captures_iter(message) {
+            let Some(whole) = caps.get(0) else {
+                continue;
+            };
+
+            // Earlier highlights win when matches overlap
+            let is_overlapping = spans
+                .iter()
+                .any(|&(start, end, _)| whole.start() < end && start < whole.end());
+
+            if whole.is_empty() || is_overlapping {
+                continue;
+            }
+
+            let mut pos = whole.start();
+
+            for (group_index, group) in caps
+                .iter()
+                .enumerate()
+                .skip(1)
+                .filter_map(|(group_index, group)| group.map(|group| (group_index, group)))
+            {
+                // Nested groups are already covered by their outer group
+                if group.is_empty() || group.start() < pos {
+                    continue;
+                }
+
+                let group_color = HIGHLIGHT_COLORS[(index + group_index) % HIGHLIGHT_COLORS.len()];
+
+                if group.start() > pos {
+                    spans.push((pos, group.start(), match_color));
+                }
+                spans.push((group.start(), group.end(), group_color));
+
+                pos = group.end();
+            }
+
+            if pos < whole.end() {
+                spans.push((pos, whole.end(), match_color));
+            }
+        }
+    }
+
+    spans
+}
+
+/// Returns the byte ranges of `message` colored by the built-in StrictMode and GC rules.
+fn get_builtin_spans(args: &CliArgs, message: &str) -> Vec<(usize, usize, Color)> {
+    let mut spans = Vec::default();
+
+    if let Some(caps) = STRICT_MODE.captures(message) {
+        for (group_index, color) in [(2, Color::Red), (3, Color::Yellow)] {
+            if let Some(group) = caps.get(group_index) {
+                spans.push((group.start(), group.end(), color));
+            }
+        }
+    }
+
+    if args.gc_color
+        && let Some(caps) = GC_COLOR.captures(message)
+    {
+        for (group_index, color) in [(2, Color::Green), (4, Color::Yellow)] {
+            if let Some(group) = caps.get(group_index) {
+                spans.push((group.start(), group.end(), color));
+            }
+        }
+    }
+
+    spans
+}
+
+/// Colors the message with the built-in rules, then the user highlights on top of them,
+/// all matched on the plain message so highlights never change the rest of the coloring.
+fn apply_message_rules(args: &CliArgs, message: &str) -> String {
+    let builtin_spans = get_builtin_spans(args, message);
+    let highlight_spans = get_highlight_spans(message, &args.highlight, args.color_depth);
+
+    if builtin_spans.is_empty() && highlight_spans.is_empty() {
+        return message.to_string();
+    }
+
+    let mut bounds = [0, message.len()]
+        .into_iter()
+        .chain(
+            builtin_spans
+                .iter()
+                .chain(&highlight_spans)
+                .flat_map(|&(start, end, _)| [start, end]),
+        )
+        .collect::<Vec<_>>();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let get_style = |pos: usize| {
+        let covering = |spans: &[(usize, usize, Color)]| {
+            spans
+                .iter()
+                .find(|&&(start, end, _)| start <= pos && pos < end)
+                .map(|&(_, _, color)| color)
+        };
+
+        match covering(&highlight_spans) {
+            Some(color) => Some((color, true)),
+            None => covering(&builtin_spans).map(|color| (color, false)),
+        }
+    };
+
+    let mut message_buffer = String::default();
+    let mut segment_start = 0;
+
+    // Adjacent ranges with the same style are written as one colored run
+    for (&start, &end) in bounds.iter().tuple_windows() {
+        if end == message.len() || get_style(end) != get_style(start) {
+            let text = &message[segment_start..end];
+
+            match get_style(segment_start) {
+                Some((color, true)) => {
+                    message_buffer.push_str(&text.color(color).bold().to_string())
+                }
+                Some((color, false)) => message_buffer.push_str(&text.color(color).to_string()),
+                None => message_buffer.push_str(text),
+            }
+
+            segment_start = end;
+        }
+    }
+
+    message_buffer
+}
+
+fn write_message(
+    message: &str,
+    tokens: &mut Vec<LineToken>,
+    header_width: usize,
+    level_foreground: Color,
+    level_background: Color,
+) {
+    write_token(
+        message,
+        tokens,
+        true,
+        header_width,
+        level_foreground,
+        level_background,
+    );
+    write_token(
+        "\n",
+        tokens,
         false,
+        header_width,
+        level_foreground,
+        level_background,
+    );
+}
+
+fn write_log_line(entry: &LogEntry, state: &mut State, args: &CliArgs, writers: &mut [Writer]) {
+    let tokens = &mut Vec::default();
+
+    render_log_line(entry, state, args, writers, tokens);
+    write_tokens(tokens, writers);
+}
+
+/// Applies the filters to a parsed logcat line, and collects the tokens of its console output.
+fn render_log_line(
+    entry: &LogEntry,
+    state: &mut State,
+    args: &CliArgs,
+    writers: &mut [Writer],
+    tokens: &mut Vec<LineToken>,
+) {
+    let header_width = &mut 0;
+
+    let owner = entry.pid.clone();
+    let tag = entry.tag.clone();
+    let level = entry.level;
+    let mut message = entry.message.clone();
+
+    let (level_foreground, level_background) = state.theme.get_level_colors(level);
+
+    if state.first_time.is_none() {
+        state.first_time = entry.time;
+    }
+
+    let layout = args
+        .layout
+        .as_ref()
+        .unwrap_or_panic("Line layout is resolved by CliArgs::parse_args");
+
+    *header_width = get_header_width(layout, args);
+
+    if write_started_process(entry, state, writers, tokens, *header_width) {
+        return;
+    }
+
+    if write_dead_process(entry, state, writers, tokens, *header_width) {
+        return;
+    }
+
+    if !args.all && !state.pids_map.contains_key(&owner) {
+        return;
+    }
+
+    if let Some(package) = state.pids_map.get(&owner)
+        && is_ignored_package(package, &state.ignored_packages)
+    {
+        return;
+    }
+
+    if level < state.log_level {
+        return;
+    }
+
+    if let Some(ignore_tag) = &args.ignore_tag
+        && is_matching_tag(&tag, ignore_tag)
+    {
+        return;
+    }
+
+    if let Some(tag_args) = &args.tag
+        && !is_matching_tag(&tag, tag_args)
+    {
+        return;
+    }
+
+    if tag == "DEBUG"
+        && let Some(_) = BACKTRACE_LINE.captures(message.trim_start())
+    {
+        message = message.trim_start().to_string();
+    }
+
+    *header_width = 0;
+
+    write_columns(
+        entry,
+        state,
+        args,
+        tokens,
+        header_width,
+        level_foreground,
+        level_background,
+    );
+
+    *header_width += 1;
+
+    let plain_message = message.clone();
+    message = apply_message_rules(args, &message);
+
+    if writers
+        .iter()
+        .any(|writer| writer.format != OutputFormat::Text)
+    {
+        let tag_color = match state.known_tokens.get(&tag) {
+            Some(&tag_color) => tag_color,
+            None => get_token_color(&tag, state, args),
+        };
+
+        write_record(
+            &LogRecord::Log {
+                timestamp: entry.timestamp.clone(),
+                pid: owner.clone(),
+                tid: entry.tid.clone(),
+                uid: entry
+                    .uid
+                    .clone()
+                    .or_else(|| state.uids_map.get(&owner).cloned()),
+                package: state.pids_map.get(&owner).cloned(),
+                tag: tag.clone(),
+                level,
+                message: plain_message.clone(),
+                source: entry.source.clone(),
+            },
+            &RecordStyle {
+                token_color: tag_color,
+                level_foreground,
+                level_background,
+                message: message.clone(),
+            },
+            writers,
+        );
+    }
+
+    write_message(
+        &message,
+        tokens,
         *header_width,
         level_foreground,
         level_background,
     );
+
+    check_stop_conditions(level, &plain_message, state, args);
 }
 
-fn apply_message_rules(args: &CliArgs, message: &str) -> String {
-    let mut message = message.to_string();
-    if STRICT_MODE.is_match(&message) {
-        message = STRICT_MODE
-            .replace(&message, |caps: &regex::Captures| {
-                format!(
-                    "{}{}{}",
-                    &caps[1],
-                    caps[2].color(Color::Red),
-                    caps[3].color(Color::Yellow)
-                )
-            })
-            .to_string();
+fn check_stop_conditions(level: LogLevel, message: &str, state: &mut State, args: &CliArgs) {
+    state.shown_lines += 1;
+
+    if let Some(fail_on) = &args.fail_on
+        && fail_on.is_matching(level, message)
+    {
+        state.exit_reason = Some(ExitReason::FailOnMatched);
+    } else if let Some(until) = &args.until
+        && until.is_match(message)
+    {
+        state.exit_reason = Some(ExitReason::UntilMatched);
+    } else if let Some(max_lines) = args.max_lines
+        && state.shown_lines >= max_lines
+    {
+        state.exit_reason = Some(ExitReason::MaxLines);
     }
+}
+
+/// Reads logcat text from TCP connections, accepted on `listener` or made to `address`,
+/// one connection at a time until CTRL+C.
+///
+/// Each connection is framed on its own, so a line cut short by a disconnection never runs
+/// into the first line of the next connection.
+fn spawn_network_reader(
+    listener: Option<TcpListener>,
+    address: String,
+    line_sender: SyncSender<(Option<String>, String)>,
+) {
+    thread::spawn(move || {
+        let mut is_retrying = false;
+
+        loop {
+            let connection = match &listener {
+                Some(listener) => listener
+                    .accept()
+                    .map(|(stream, peer)| (stream, peer.to_string())),
+                None => TcpStream::connect(&address).map(|stream| (stream, address.clone())),
+            };
+
+            let (stream, peer) = match connection {
+                Ok(connection) => connection,
+                Err(err) => {
+                    if !is_retrying {
+                        let message = if listener.is_some() {
+                            format!(
+                                "Could not accept connection on {address}: {err}, retrying{}",
+                                *ELLIPSIS
+                            )
+                        } else {
+                            format!(
+                                "Could not connect to {address}: {err}, retrying{}",
+                                *ELLIPSIS
+                            )
+                        };
+                        status!("{}", message.yellow().bold());
+                    }
+
+                    is_retrying = true;
+                    thread::sleep(RECONNECT_INTERVAL);
+
+                    continue;
+                }
+            };
+
+            is_retrying = false;
+
+            let message = format!("Connected to {peer}").cyan().bold();
+            status!("{message}");
+
+            if let Ok(source) = CompressionFormat::decompress(Box::new(stream)) {
+                let mut reader = BufReader::new(source);
 
-    if args.gc_color && GC_COLOR.is_match(&message) {
-        message = GC_COLOR
-            .replace(&message, |caps: &regex::Captures| {
+                loop {
+                    let buffer = &mut vec![];
+                    let result = reader.read_until(b'\n', buffer);
+
+                    if !buffer.is_empty() {
+                        let line = String::from_utf8_lossy(buffer)
+                            .trim_end_matches(['\r', '\n'])
+                            .to_string();
+
+                        if line_sender.send((None, line)).is_err() {
+                            return;
+                        }
+                    }
+
+                    if !matches!(result, Ok(bytes_read) if bytes_read > 0) {
+                        break;
+                    }
+                }
+            }
+
+            let message = if listener.is_some() {
                 format!(
-                    "{}{}{}{}",
-                    &caps[1],
-                    caps[2].color(Color::Green),
-                    &caps[3],
-                    caps[4].color(Color::Yellow)
+                    "Connection from {peer} closed, waiting for a new one{}",
+                    *ELLIPSIS
                 )
-            })
-            .to_string();
+            } else {
+                format!("Connection to {peer} closed, reconnecting{}", *ELLIPSIS)
+            };
+            status!("{}", message.yellow().bold());
+
+            if listener.is_none() {
+                thread::sleep(RECONNECT_INTERVAL);
+            }
+        }
+    });
+}
+
+/// Opens a log file given with `--file`, exits when it cannot be read.
+fn open_log_file(path: &str) -> Box<dyn Read + Send> {
+    match File::open(path) {
+        Ok(file) => Box::new(file),
+        Err(err) => {
+            let err_code = err.raw_os_error().unwrap_or(1);
+            let err_hdr = format!("ERROR: {err}").red().bold();
+            let err_msg = format!("Could not open log file {path}").red().bold();
+
+            eprintln!("{err_hdr}");
+            eprintln!("{err_msg}");
+            exit(err_code);
+        }
+    }
+}
+
+/// Opens a file given with `--file`, Android Studio captures are converted to logcat lines
+/// and their processes are added to the known ones, since they hold no process events.
+fn open_input_file(path: &str, state: &mut State, args: &CliArgs) -> Box<dyn Read + Send> {
+    let source = open_log_file(path);
+
+    if !LogcatFile::is_logcat_path(path) {
+        return source;
+    }
+
+    let logcat_file = CompressionFormat::decompress(source)
+        .map_err(|err| err.to_string())
+        .and_then(|source| {
+            serde_json::from_reader::<_, LogcatFile>(source).map_err(|err| err.to_string())
+        });
+    let logcat_file = match logcat_file {
+        Ok(logcat_file) => logcat_file,
+        Err(err) => {
+            let err_hdr = format!("ERROR: {err}").red().bold();
+            let err_msg = format!("Could not read Android Studio capture {path}")
+                .red()
+                .bold();
+
+            eprintln!("{err_hdr}");
+            eprintln!("{err_msg}");
+            exit(1);
+        }
+    };
+
+    for message in &logcat_file.logcat_messages {
+        let header = &message.header;
+
+        if args.all
+            || is_matching_package(
+                &header.process_name,
+                &state.named_processes,
+                &state.catchall_package,
+            )
+        {
+            state
+                .pids_map
+                .insert(header.pid.to_string(), header.process_name.clone());
+        }
     }
 
-    message
+    let lines = logcat_file
+        .logcat_messages
+        .iter()
+        .flat_map(|message| message.get_lines())
+        .join("\n");
+
+    status!(
+        "{}",
+        format!(
+            "Imported {} messages from {path}",
+            logcat_file.logcat_messages.len()
+        )
+        .cyan()
+        .bold()
+    );
+
+    Box::new(Cursor::new(lines.into_bytes()))
+}
+
+/// Reads the thread names of the requested pids with `adb shell ps -T`, so the render loop
+/// never waits for the device, and sends them to the main loop.
+fn spawn_thread_name_resolver(
+    base_adb_command: Vec<String>,
+    log_sender: SyncSender<LogEvent>,
+) -> Sender<String> {
+    let (pid_sender, pid_receiver) = channel::<String>();
+
+    thread::spawn(move || {
+        for pid in pid_receiver {
+            let thread_names = get_thread_names(&base_adb_command, &pid);
+
+            if log_sender
+                .send(LogEvent::ThreadNames(pid, thread_names))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    pid_sender
+}
+
+/// Reads the lines of the log source as fast as they come, so the source is never held
+/// back by parsing nor rendering, and queues them for the parser thread along with the
+/// name of the source.
+fn spawn_log_reader(
+    source: Box<dyn Read + Send>,
+    source_name: Option<String>,
+    line_sender: SyncSender<(Option<String>, String)>,
+) {
+    thread::spawn(move || {
+        let source = CompressionFormat::decompress(source)
+            .unwrap_or_panic("Failed to detect stream compression");
+        let mut reader = BufReader::new(source);
+
+        loop {
+            let buffer = &mut vec![];
+            let bytes_read = reader
+                .read_until(b'\n', buffer)
+                .unwrap_or_panic("Error reading stream");
+
+            if bytes_read == 0 {
+                break;
+            }
+
+            let line = String::from_utf8_lossy(buffer)
+                .trim_end_matches(['\r', '\n'])
+                .to_string();
+
+            if line_sender.send((source_name.clone(), line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Reads several log files at once and queues their lines for the parser thread in
+/// chronological order, see [LogMerger].
+fn spawn_merged_log_reader(
+    sources: Vec<Box<dyn Read + Send>>,
+    source_names: Vec<String>,
+    line_sender: SyncSender<(Option<String>, String)>,
+) {
+    thread::spawn(move || {
+        let readers = sources
+            .into_iter()
+            .map(|source| {
+                let source = CompressionFormat::decompress(source)
+                    .unwrap_or_panic("Failed to detect stream compression");
+
+                Box::new(BufReader::new(source)) as Box<dyn BufRead + Send>
+            })
+            .collect();
+
+        let merger = LogMerger::new(readers).unwrap_or_panic("Error reading stream");
+
+        for merged_line in merger {
+            let (index, line) = merged_line.unwrap_or_panic("Error reading stream");
+
+            if line_sender
+                .send((source_names.get(index).cloned(), line))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+}
+
+/// Reads a session file saved with `--record` and queues its lines for the parser thread,
+/// waiting between them as long as they originally did, divided by `speed`.
+fn spawn_replay_reader(
+    source: Box<dyn Read + Send>,
+    speed: f64,
+    line_sender: SyncSender<(Option<String>, String)>,
+) {
+    thread::spawn(move || {
+        let source = CompressionFormat::decompress(source)
+            .unwrap_or_panic("Failed to detect stream compression");
+        let replay_start = Instant::now();
+
+        for (index, json) in BufReader::new(source).lines().enumerate() {
+            let json = json.unwrap_or_panic("Error reading session");
+
+            if json.trim().is_empty() {
+                continue;
+            }
+
+            let err_msg = format!("Invalid session record on line {}", index + 1);
+            let record = serde_json::from_str::<SessionRecord>(&json).unwrap_or_panic(&err_msg);
+
+            let (elapsed_ms, line) = match record {
+                SessionRecord::Line { elapsed_ms, line } => (elapsed_ms, line),
+                SessionRecord::Metadata {
+                    recorded_at,
+                    serial,
+                    manufacturer,
+                    model,
+                    android_version,
+                    ..
+                } => {
+                    let device = [manufacturer, model, serial]
+                        .into_iter()
+                        .flatten()
+                        .chain(android_version.map(|version| format!("Android {version}")))
+                        .join(" ");
+                    let device = if device.is_empty() {
+                        String::default()
+                    } else {
+                        format!(" from {device}")
+                    };
+
+                    let message = format!("Replaying session recorded at {recorded_at}{device}")
+                        .cyan()
+                        .bold();
+                    status!("{message}");
+
+                    continue;
+                }
+            };
+
+            let due = Duration::from_secs_f64(elapsed_ms as f64 / 1000.0 / speed);
+            thread::sleep(due.saturating_sub(replay_start.elapsed()));
+
+            if line_sender.send((None, line)).is_err() {
+                break;
+            }
+        }
+    });
 }
 
-fn write_message(
-    message: &str,
-    writers: &mut [Writer],
-    header_width: usize,
-    level_foreground: Color,
-    level_background: Color,
+/// Parses the lines queued by the reader thread and queues them for the main loop, which
+/// filters, renders and writes them.
+///
+/// Sets [FALLING_BEHIND] when the main loop queue is full.
+fn spawn_log_parser(
+    line_receiver: Receiver<(Option<String>, String)>,
+    log_sender: SyncSender<LogEvent>,
 ) {
-    write_token(
-        message,
-        writers,
-        true,
-        header_width,
-        level_foreground,
-        level_background,
-    );
-    write_token(
-        "\n",
-        writers,
-        false,
-        header_width,
-        level_foreground,
-        level_background,
-    );
-}
+    thread::spawn(move || {
+        // Yearless timestamps get their year from the line before them in the same source
+        let mut last_times: HashMap<Option<String>, NaiveDateTime> = HashMap::default();
 
-fn write_log_line(line: &str, state: &mut State, args: &CliArgs, writers: &mut [Writer]) {
-    let base_level_size = 1 + 1 + 3;
-    let header_width = &mut 0;
+        for (source_name, line) in line_receiver {
+            let entry = if NATIVE_TAGS_LINE.is_match(&line) {
+                None
+            } else {
+                LogEntry::parse(&line).map(|entry| {
+                    let time = entry.get_time_after(last_times.get(&source_name).copied());
 
-    if NATIVE_TAGS_LINE.is_match(line) {
-        return;
-    }
+                    if let Some(time) = time {
+                        last_times.insert(source_name.clone(), time);
+                    }
 
-    let log_line = match LOG_LINE.captures(line) {
-        Some(cap) => cap,
-        None => return,
-    };
+                    LogEntry {
+                        time,
+                        source: source_name,
+                        ..entry
+                    }
+                })
+            };
 
-    let owner = log_line
-        .get(3)
-        .map_or(String::default(), |mat| mat.as_str().to_string())
-        .trim()
-        .to_string();
-
-    let tag = log_line
-        .get(2)
-        .map_or(String::default(), |mat| mat.as_str().to_string())
-        .trim()
-        .to_string();
-
-    let level = log_line
-        .get(1)
-        .map_or(LogLevel::default(), |mat| LogLevel::from(mat.as_str()));
-
-    let mut message = log_line
-        .get(4)
-        .map_or(String::default(), |mat| mat.as_str().to_string())
-        .trim()
-        .to_string();
-
-    let level_foreground = Color::Black;
-
-    let level_background = match level {
-        LogLevel::DEBUG => Color::BrightBlue,
-        LogLevel::INFO => Color::BrightGreen,
-        LogLevel::WARN => Color::BrightYellow,
-        LogLevel::ERROR => Color::TrueColor {
-            r: 255,
-            g: 100,
-            b: 0,
-        }, // DarkOrange
-        LogLevel::FATAL => Color::BrightRed,
-        LogLevel::VERBOSE => Color::BrightCyan,
-    };
+            QUEUED_LINES.fetch_add(1, Ordering::Relaxed);
 
-    if args.show_pid {
-        *header_width += args.pid_width as usize
-    }
+            let log_event = match log_sender.try_send(LogEvent::Line(line, entry)) {
+                Ok(()) => continue,
+                Err(TrySendError::Full(log_event)) => log_event,
+                Err(TrySendError::Disconnected(_)) => return,
+            };
 
-    if args.show_package {
-        *header_width += args.package_width as usize
-    }
+            FALLING_BEHIND.store(true, Ordering::Relaxed);
 
-    *header_width += (2 + args.tag_width + base_level_size) as usize;
+            if log_sender.send(log_event).is_err() {
+                return;
+            }
+        }
 
-    if write_started_process(line, state, writers, *header_width) {
-        return;
-    }
+        let _ = log_sender.send(LogEvent::EndOfStream);
+    });
+}
 
-    if write_dead_process(&tag, &message, state, writers, *header_width) {
-        return;
-    }
+/// Reads the stderr of the log source alongside its stdout, so errors are shown as they
+/// happen and a full stderr pipe never blocks the source.
+fn spawn_error_reader(
+    source: Box<dyn Read + Send>,
+    log_sender: SyncSender<LogEvent>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(source).lines() {
+            let Ok(line) = line else { break };
+
+            if !line.trim().is_empty() && log_sender.send(LogEvent::Error(line)).is_err() {
+                break;
+            }
+        }
+    })
+}
 
-    if !args.all && !state.pids_map.contains_key(&owner) {
-        return;
-    }
+fn spawn_wrapped_command(
+    command: &[String],
+    log_sender: SyncSender<LogEvent>,
+) -> Arc<Mutex<Child>> {
+    let command_str = command.join(" ");
+    let message = format!("Running {command_str}{}", *ELLIPSIS).cyan().bold();
+    status!("{message}");
+
+    let child = match Command::new(&command[0]).args(&command[1..]).spawn() {
+        Ok(child) => Arc::new(Mutex::new(child)),
+        Err(err) => {
+            let err_hdr = format!("ERROR: {err}").red().bold();
+            let err_msg = format!("Could not run {command_str}").red().bold();
 
-    if level < state.log_level {
-        return;
-    }
+            eprintln!("{err_hdr}");
+            eprintln!("{err_msg}");
+            exit(ExitReason::COMMAND_NOT_FOUND);
+        }
+    };
 
-    if let Some(ignore_tag) = &args.ignore_tag
-        && is_matching_tag(&tag, ignore_tag)
-    {
-        return;
-    }
+    let waited_child = Arc::clone(&child);
 
-    if let Some(tag_args) = &args.tag
-        && !is_matching_tag(&tag, tag_args)
-    {
-        return;
-    }
+    thread::spawn(move || {
+        loop {
+            let exit_status = waited_child
+                .lock()
+                .unwrap_or_panic("Failed to lock wrapped command")
+                .try_wait();
 
-    if tag == "DEBUG"
-        && let Some(_) = BACKTRACE_LINE.captures(message.trim_start())
-    {
-        message = message.trim_start().to_string();
-    }
+            match exit_status {
+                Ok(Some(status)) => {
+                    // Give logcat a moment to deliver the last lines of the command
+                    thread::sleep(COMMAND_EXIT_GRACE_PERIOD);
 
-    *header_width = 0;
+                    let exit_code = get_command_exit_code(status);
+                    let _ = log_sender.send(LogEvent::CommandExited(exit_code));
+                    break;
+                }
+                Ok(None) => thread::sleep(COMMAND_POLL_INTERVAL),
+                Err(_) => {
+                    let _ = log_sender.send(LogEvent::CommandExited(1));
+                    break;
+                }
+            }
+        }
+    });
 
-    write_pid(
-        state,
-        args,
-        writers,
-        header_width,
-        &owner,
-        level_foreground,
-        level_background,
-    );
+    child
+}
 
-    write_package_name(
-        &owner,
-        args,
-        state,
-        writers,
-        header_width,
-        level_foreground,
-        level_background,
-    );
+/// Returns the exit code of the command given after `--`, `128 + signal` when a signal
+/// killed it like shells report it.
+#[cfg(unix)]
+fn get_command_exit_code(status: ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
 
-    write_tag(
-        &tag,
-        args,
-        state,
-        writers,
-        header_width,
-        level_foreground,
-        level_background,
-    );
+    status
+        .code()
+        .or(status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1)
+}
 
-    write_log_level(
-        level,
-        args,
-        writers,
-        header_width,
-        level_foreground,
-        level_background,
-    );
+/// Returns the exit code of the command given after `--`.
+#[cfg(not(unix))]
+fn get_command_exit_code(status: ExitStatus) -> i32 {
+    status.code().unwrap_or(1)
+}
 
-    *header_width += base_level_size as usize;
+fn stop_wrapped_command(wrapped_command: Arc<Mutex<Child>>, exit_reason: ExitReason) -> ExitReason {
+    let mut child = wrapped_command
+        .lock()
+        .unwrap_or_panic("Failed to lock wrapped command");
 
-    message = apply_message_rules(args, &message);
+    match exit_reason {
+        ExitReason::CommandExited(_) => exit_reason,
 
-    write_message(
-        &message,
-        writers,
-        *header_width,
-        level_foreground,
-        level_background,
-    );
+        // Logs ended on their own, the command is still the one deciding the exit code
+        ExitReason::StreamEnded => {
+            let message = format!("Waiting for command {} to exit{}", child.id(), *ELLIPSIS)
+                .cyan()
+                .bold();
+            status!("{message}");
+
+            let wait_fail_message = format!("Failed to wait for command {}", child.id())
+                .red()
+                .bold();
+            let status = child.wait().unwrap_or_panic(&wait_fail_message);
+
+            ExitReason::CommandExited(get_command_exit_code(status))
+        }
+
+        _ => {
+            let kill_fail_message = format!("Failed to kill command {}", child.id())
+                .red()
+                .bold();
+
+            child.kill().unwrap_or_panic(&kill_fail_message);
+            let _ = child.wait();
+
+            exit_reason
+        }
+    }
 }
 
 fn panic_hook(info: &PanicHookInfo) {
@@ -1396,10 +2585,29 @@ fn panic_hook(info: &PanicHookInfo) {
 }
 
 fn ctrlc_handler() {
+    let interrupt_sender = INTERRUPT_SENDER
+        .lock()
+        .ok()
+        .and_then(|mut interrupt_sender| interrupt_sender.take());
+
+    // A second CTRL+C exits right away in case the main loop is stuck
+    if let Some(interrupt_sender) = interrupt_sender {
+        INTERRUPTED.store(true, Ordering::Relaxed);
+
+        // Wakes the main loop up while it waits for lines, a full queue means it is busy
+        // handling them and sees INTERRUPTED before the next one
+        if !matches!(
+            interrupt_sender.try_send(LogEvent::Interrupted),
+            Err(TrySendError::Disconnected(_))
+        ) {
+            return;
+        }
+    }
+
     let bin_name = env!("CARGO_BIN_NAME").cyan().bold();
     let message = "Stopped by user.".cyan().bold();
 
-    println!("{bin_name} {message}");
+    status!("{bin_name} {message}");
     exit(0);
 }
 
@@ -1410,12 +2618,43 @@ fn main() {
     let mut adb_child = None;
 
     let args = &mut CliArgs::parse_args();
+
+    STATUS_TO_STDERR.store(args.format != OutputFormat::Text, Ordering::Relaxed);
+    STATUS_COLORS.store(!args.no_color, Ordering::Relaxed);
+    colored::control::set_override(!args.no_color);
+
+    // colored downgrades 24-bit colors unless COLORTERM says otherwise, which is not set
+    // by Windows Terminal nor when the depth is given with --color-depth
+    if args.color_depth == ColorDepth::TrueColor
+        && !matches!(env::var("COLORTERM").as_deref(), Ok("truecolor" | "24bit"))
+    {
+        // SAFETY: only the CTRL+C handler thread is running, and it does not read the environment
+        unsafe { env::set_var("COLORTERM", "truecolor") };
+    }
     let stdin = stdin();
+    // Logs are read from ADB unless they are piped into stdin, read from files or the network,
+    // or replayed
+    let is_live = args.files.is_none()
+        && args.replay_path.is_none()
+        && args.listen_address.is_none()
+        && args.connect_address.is_none()
+        && stdin.is_terminal();
     let base_adb_command = &get_adb_command(args);
-    let logcat_command = ["logcat", "-v", "brief"].map(|item| item.to_string());
+    let logcat_command = ["logcat", "-v", "threadtime"].map(|item| item.to_string());
     let adb_command = &mut base_adb_command.clone();
     let console_width = get_console_width();
-    let stdout_writer = Writer::new_console(console_width, !args.no_color);
+    watch_console_resize();
+    // Wrapping to the console width only makes sense when stdout is a terminal
+    let stdout_writer = Writer::new_console(
+        if stdout().is_terminal() {
+            console_width
+        } else {
+            -1
+        },
+        !args.no_color,
+        args.word_wrap,
+        args.format,
+    );
     let writers = &mut vec![stdout_writer];
     let packages = &mut args
         .packages
@@ -1426,7 +2665,7 @@ fn main() {
     adb_command.extend(logcat_command);
 
     let message = "Starting ADB server...".cyan().bold();
-    println!("{message}");
+    status!("{message}");
 
     if let Err(err) = start_adb_server(base_adb_command) {
         let err_code = err.raw_os_error().unwrap_or(1);
@@ -1436,9 +2675,12 @@ fn main() {
                 .red()
                 .bold();
 
-        eprintln!("{err_hdr}");
-        eprintln!("{err_msg}");
-        exit(err_code);
+        // Logs piped into stdin or read from files are read without ADB
+        if is_live {
+            eprintln!("{err_hdr}");
+            eprintln!("{err_msg}");
+            exit(err_code);
+        }
     }
 
     match get_adb_devices(base_adb_command) {
@@ -1446,7 +2688,7 @@ fn main() {
         Some(devices) => {
             for (index, device) in devices.iter().enumerate() {
                 let message = format!("Found Device #{index}: {device:?}").cyan().bold();
-                println!("{message}");
+                status!("{message}");
             }
         }
 
@@ -1459,7 +2701,7 @@ fn main() {
                 .red()
                 .bold();
 
-            if stdin.is_terminal() {
+            if is_live {
                 eprintln!("{err_hdr}");
                 eprintln!("{err_msg}");
                 exit(err_code);
@@ -1468,8 +2710,10 @@ fn main() {
     }
 
     if args.ignore_system_tags {
-        let mut system_tags: Vec<String> =
-            SYSTEM_TAGS.iter().map(|tag| format!("^{tag}$")).collect();
+        let mut system_tags: Vec<String> = get_system_tags(base_adb_command, args)
+            .iter()
+            .map(|tag| format!("^{tag}$"))
+            .collect();
         args.ignore_tag = match args.ignore_tag.as_mut() {
             Some(existing) => {
                 existing.append(&mut system_tags);
@@ -1501,11 +2745,40 @@ fn main() {
     }
 
     if let Some(path) = args.output_path.clone() {
-        let file_writer =
-            Writer::new_file(File::create(path).unwrap_or_panic("Failed to create output file"));
+        let format = OutputFormat::from_path(&path).unwrap_or(args.format);
+        let rotation = args.get_rotation();
+        let file_writer = if rotation.is_enabled() {
+            Writer::new_rotating_file(&path, format, rotation)
+        } else {
+            Writer::new_file(&path, format)
+        };
         writers.push(file_writer);
     }
 
+    // Kept apart from the other writers, which only receive the filtered and formatted lines
+    let mut raw_writer = args
+        .raw_output_path
+        .as_ref()
+        .map(|path| Writer::new_file(path, OutputFormat::Text));
+
+    let recording_start = Instant::now();
+    let mut session_writer = args.record_path.as_ref().map(|path| {
+        let mut session_writer = Writer::new_file(path, OutputFormat::Text);
+        let metadata = get_session_metadata(base_adb_command, is_live);
+
+        write_session_record(&mut session_writer, &metadata);
+        session_writer
+    });
+
+    if writers
+        .iter()
+        .any(|writer| writer.format == OutputFormat::Html)
+    {
+        // HTML messages are rendered from the ANSI codes of the highlights, which colored
+        // does not emit when console colors are disabled
+        colored::control::set_override(true);
+    }
+
     if args.current_app
         && let Some(running_packages) = get_current_app_package(base_adb_command)
         && !running_packages.is_empty()
@@ -1522,9 +2795,9 @@ fn main() {
         adb_command.extend(["-e".to_string(), regex]);
     }
 
-    if !args.keep_logcat && stdin.is_terminal() {
+    if !args.keep_logcat && is_live {
         let message = format!("Clearing logcat{}", *ELLIPSIS).cyan().bold();
-        println!("{message}");
+        status!("{message}");
 
         let clear_cmd = [
             base_adb_command.clone(),
@@ -1534,6 +2807,13 @@ fn main() {
         let _ = Command::new(&clear_cmd[0]).args(&clear_cmd[1..]).output();
     }
 
+    for writer in writers.iter_mut() {
+        if writer.format == OutputFormat::Logcat {
+            writer.logcat_metadata.project_application_ids =
+                packages.iter().sorted().cloned().collect();
+        }
+    }
+
     let catchall_package = &packages
         .iter()
         .filter(|package| !package.contains(':'))
@@ -1550,40 +2830,47 @@ fn main() {
         args.all = true;
     }
 
-    let pids_map = get_processes(base_adb_command, catchall_package, args);
-
-    let tag_colors = vec![
-        Color::BrightRed,
-        Color::BrightBlue,
-        Color::BrightCyan,
-        Color::BrightGreen,
-        Color::BrightYellow,
-        Color::BrightMagenta,
-    ];
-
-    let known_tags = HashMap::from([
-        ("jdwp".to_string(), Color::White),
-        ("DEBUG".to_string(), Color::Yellow),
-        ("Process".to_string(), Color::White),
-        ("dalvikvm".to_string(), Color::White),
-        ("StrictMode".to_string(), Color::White),
-        ("AndroidRuntime".to_string(), Color::Cyan),
-        ("ActivityThread".to_string(), Color::White),
-        ("ActivityManager".to_string(), Color::White),
-    ]);
+    let ignored_packages = args
+        .ignore_package
+        .iter()
+        .flatten()
+        .flat_map(|package_arg| package_arg.split(','))
+        .map(|package| package.trim().to_string())
+        .filter(|package| !package.is_empty())
+        .collect::<Vec<_>>();
+
+    let theme = get_theme(args);
 
     let mut state = State {
-        pids_map,
+        pids_map: HashMap::default(),
         last_tag: None,
         app_pid: None,
         log_level: args.log_level,
         named_processes,
         catchall_package: catchall_package.clone(),
-        token_colors: tag_colors,
-        known_tokens: known_tags,
+        ignored_packages,
+        token_colors: theme.palette.clone(),
+        known_tokens: theme.tags.clone(),
+        visible_tokens: Vec::default(),
+        visible_tags: Vec::default(),
+        uids_map: HashMap::default(),
+        shown_lines: 0,
+        exit_reason: None,
+        first_time: None,
+        last_shown_time: None,
+        start_times: HashMap::default(),
+        thread_names: HashMap::default(),
+        missing_threads: HashMap::default(),
+        thread_lookup_times: HashMap::default(),
+        thread_name_requests: None,
+        theme,
     };
 
-    if stdin.is_terminal() {
+    // Captures, replays, piped and remote logs start empty and learn their processes from their
+    // own process start lines, the attached device has nothing to do with them
+    if is_live {
+        state.pids_map = get_processes(base_adb_command, &state, args);
+
         adb_child = Some(
             Command::new(&adb_command[0])
                 .args(&adb_command[1..])
@@ -1596,31 +2883,134 @@ fn main() {
 
     let mut log_source = if let Some(adb_child) = adb_child {
         LogSource::Process(adb_child)
+    } else if let Some(files) = args.files.clone() {
+        LogSource::Files(files)
+    } else if let Some(replay_path) = args.replay_path.clone() {
+        LogSource::Replay(replay_path)
+    } else if let Some(listen_address) = args.listen_address.clone() {
+        LogSource::Listen(listen_address)
+    } else if let Some(connect_address) = args.connect_address.clone() {
+        LogSource::Connect(connect_address)
     } else {
         LogSource::Stdin
     };
 
-    let (stdout_source, stderr_source) = match log_source {
+    let (line_sender, line_receiver) = sync_channel(LINE_QUEUE_CAPACITY);
+    let (log_sender, log_receiver) = sync_channel(LINE_QUEUE_CAPACITY);
+    let source_names = args.get_source_names();
+
+    let stderr_source = match log_source {
         LogSource::Process(ref mut child) => {
             let stdout = child
                 .stdout
                 .take()
-                .map(|stdout| Box::new(stdout) as Box<dyn Read>)
+                .map(|stdout| Box::new(stdout) as Box<dyn Read + Send>)
                 .unwrap_or_panic("Failed to capture stdout");
 
-            let stderr = child
+            spawn_log_reader(stdout, None, line_sender);
+
+            child
                 .stderr
                 .take()
-                .map(|stderr| Box::new(stderr) as Box<dyn Read>);
+                .map(|stderr| Box::new(stderr) as Box<dyn Read + Send>)
+        }
+
+        LogSource::Stdin => {
+            spawn_log_reader(Box::new(stdin), None, line_sender);
+
+            None
+        }
 
-            (stdout, stderr)
+        // Followed files never end, so they are shown as their lines arrive, Android Studio
+        // captures are saved once and never grow
+        LogSource::Files(ref files) if args.follow => {
+            for (index, path) in files.iter().enumerate() {
+                let source = if LogcatFile::is_logcat_path(path) {
+                    open_input_file(path, &mut state, args)
+                } else {
+                    Box::new(FollowedFile::open(path))
+                };
+
+                spawn_log_reader(
+                    source,
+                    source_names.get(index).cloned(),
+                    line_sender.clone(),
+                );
+            }
+
+            drop(line_sender);
+
+            None
+        }
+
+        LogSource::Files(ref files) => {
+            let sources = files
+                .iter()
+                .map(|path| open_input_file(path, &mut state, args))
+                .collect();
+            spawn_merged_log_reader(sources, source_names, line_sender);
+
+            None
+        }
+
+        LogSource::Replay(ref path) => {
+            spawn_replay_reader(open_log_file(path), args.speed, line_sender);
+
+            None
+        }
+
+        LogSource::Listen(ref address) => {
+            let listener = match TcpListener::bind(address) {
+                Ok(listener) => listener,
+                Err(err) => {
+                    let err_code = err.raw_os_error().unwrap_or(1);
+                    let err_hdr = format!("ERROR: {err}").red().bold();
+                    let err_msg = format!("Could not listen on {address}").red().bold();
+
+                    eprintln!("{err_hdr}");
+                    eprintln!("{err_msg}");
+                    exit(err_code);
+                }
+            };
+
+            let message = format!("Listening for logs on {address}{}", *ELLIPSIS)
+                .cyan()
+                .bold();
+            status!("{message}");
+
+            spawn_network_reader(Some(listener), address.clone(), line_sender);
+
+            None
         }
 
-        LogSource::Stdin => (Box::new(stdin) as Box<dyn Read>, None),
+        LogSource::Connect(ref address) => {
+            spawn_network_reader(None, address.clone(), line_sender);
+
+            None
+        }
     };
 
-    let mut stdout = BufReader::new(stdout_source);
-    let mut stderr = stderr_source.map(BufReader::new);
+    spawn_log_parser(line_receiver, log_sender.clone());
+
+    let error_reader = stderr_source.map(|source| spawn_error_reader(source, log_sender.clone()));
+
+    // Captures and remote logs may come from another device than the attached one
+    if args.thread_names && matches!(log_source, LogSource::Process(_)) {
+        state.thread_name_requests = Some(spawn_thread_name_resolver(
+            base_adb_command.clone(),
+            log_sender.clone(),
+        ));
+    }
+
+    *INTERRUPT_SENDER
+        .lock()
+        .unwrap_or_panic("Failed to lock interrupt sender") = Some(log_sender.clone());
+
+    let wrapped_command = if !args.command.is_empty() {
+        Some(spawn_wrapped_command(&args.command, log_sender))
+    } else {
+        None
+    };
 
     let message = if !packages.is_empty() {
         let packages_vec = packages.iter().cloned().collect::<Vec<_>>();
@@ -1638,70 +3028,157 @@ fn main() {
             .bold()
     };
 
-    println!("{message}");
+    status!("{message}");
 
-    loop {
-        if let LogSource::Process(ref mut adb_child) = log_source {
-            let exit_status = adb_child.try_wait();
+    let deadline = args.duration.map(|duration| Instant::now() + duration);
 
-            match exit_status {
-                Ok(exit_status) => {
-                    if let Some(status) = exit_status {
-                        let message = format!(
-                            "Child process {} exited with status: {status}",
-                            adb_child.id()
-                        )
-                        .cyan()
-                        .bold();
+    let mut last_flush = Instant::now();
+    let mut last_falling_behind_warning: Option<Instant> = None;
 
-                        println!("{message}");
-                        break;
-                    }
+    let exit_reason = loop {
+        if INTERRUPTED.load(Ordering::Relaxed) {
+            break ExitReason::Interrupted;
+        }
+
+        if FALLING_BEHIND.swap(false, Ordering::Relaxed)
+            && last_falling_behind_warning.is_none_or(|last_warning| {
+                last_warning.elapsed() >= FALLING_BEHIND_WARNING_INTERVAL
+            })
+        {
+            flush_writers(writers, &mut raw_writer, &mut session_writer);
+            last_falling_behind_warning = Some(Instant::now());
+
+            let message = format!(
+                "WARNING: Output is falling behind, {} lines are queued{}",
+                QUEUED_LINES.load(Ordering::Relaxed),
+                *ELLIPSIS
+            )
+            .yellow()
+            .bold();
+            status!("{message}");
+        }
+
+        let pending_event = log_receiver.try_recv().ok();
+
+        // Lines are flushed once no more are pending, or regularly while the device is busy
+        if pending_event.is_none() || last_flush.elapsed() >= FLUSH_INTERVAL {
+            flush_writers(writers, &mut raw_writer, &mut session_writer);
+            last_flush = Instant::now();
+        }
+
+        let log_event = match (pending_event, deadline) {
+            (Some(log_event), _) => log_event,
+            (None, Some(deadline)) => {
+                match log_receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                {
+                    Ok(log_event) => log_event,
+                    Err(RecvTimeoutError::Timeout) => break ExitReason::Timeout,
+                    Err(RecvTimeoutError::Disconnected) => LogEvent::EndOfStream,
                 }
-                Err(err) => {
-                    let message = format!(
-                        "Failed to wait for child process {}: {}",
-                        adb_child.id(),
-                        err
-                    )
-                    .red()
-                    .bold();
-
-                    eprintln!("{message}");
-                    break;
+            }
+            (None, None) => log_receiver.recv().unwrap_or(LogEvent::EndOfStream),
+        };
+
+        match log_event {
+            LogEvent::Line(line, entry) => {
+                QUEUED_LINES.fetch_sub(1, Ordering::Relaxed);
+
+                if let Some(ref mut raw_writer) = raw_writer {
+                    raw_writer.write(&line);
+                    raw_writer.write("\n");
+                }
+
+                if let Some(ref mut session_writer) = session_writer {
+                    let record = SessionRecord::Line {
+                        elapsed_ms: recording_start.elapsed().as_millis() as u64,
+                        line: line.clone(),
+                    };
+
+                    write_session_record(session_writer, &record);
+                }
+
+                for writer in writers.iter_mut() {
+                    writer.rotate_if_needed();
+                }
+
+                if let Some(entry) = entry {
+                    write_log_line(&entry, &mut state, args, writers);
+                }
+
+                if let Some(exit_reason) = state.exit_reason {
+                    break exit_reason;
                 }
             }
-        }
 
-        let stdout_buffer = &mut vec![];
-        let stderr_buffer = &mut vec![];
+            LogEvent::ThreadNames(pid, thread_names) => {
+                // The process may have died while its names were read
+                if let Some(missing_threads) = state.missing_threads.get_mut(&pid) {
+                    missing_threads.retain(|tid| !thread_names.contains_key(tid));
+                    state
+                        .thread_names
+                        .entry(pid)
+                        .or_default()
+                        .extend(thread_names);
+                }
+            }
 
-        let stdout_bytes_read = stdout
-            .read_until(b'\n', stdout_buffer)
-            .unwrap_or_panic("Error reading stream");
+            LogEvent::CommandExited(exit_code) => break ExitReason::CommandExited(exit_code),
 
-        if stdout_bytes_read == 0 {
-            if let Some(ref mut stderr) = stderr
-                && let Ok(stderr_bytes_read) = stderr.read_to_end(stderr_buffer)
-                && stderr_bytes_read > 0
-            {
-                let err = String::from_utf8_lossy(stderr_buffer)
-                    .trim_end_matches(['\r', '\n'])
-                    .red()
-                    .bold();
+            LogEvent::Interrupted => break ExitReason::Interrupted,
 
-                let err_msg = format!("Error reading stream:\n{}", err).red().bold();
+            LogEvent::Error(err) => {
+                flush_writers(writers, &mut raw_writer, &mut session_writer);
+
+                let err_msg = format!("Error reading stream: {err}").red().bold();
                 eprintln!("{err_msg}");
             }
 
-            break;
-        }
+            LogEvent::EndOfStream => {
+                flush_writers(writers, &mut raw_writer, &mut session_writer);
 
-        let line = String::from_utf8_lossy(stdout_buffer)
-            .trim_end_matches(['\r', '\n'])
-            .to_string();
-        write_log_line(&line, &mut state, args, writers);
-    }
+                // The source usually writes its last errors right before closing stdout
+                if let Some(error_reader) = error_reader {
+                    let _ = error_reader.join();
+
+                    for log_event in log_receiver.try_iter() {
+                        if let LogEvent::Error(err) = log_event {
+                            let err_msg = format!("Error reading stream: {err}").red().bold();
+                            eprintln!("{err_msg}");
+                        }
+                    }
+                }
+
+                if let LogSource::Process(ref mut adb_child) = log_source {
+                    match adb_child.try_wait() {
+                        Ok(Some(status)) => {
+                            let message = format!(
+                                "Child process {} exited with status: {status}",
+                                adb_child.id()
+                            )
+                            .cyan()
+                            .bold();
+
+                            status!("{message}");
+                        }
+                        Ok(None) => {}
+                        Err(err) => {
+                            let message = format!(
+                                "Failed to wait for child process {}: {}",
+                                adb_child.id(),
+                                err
+                            )
+                            .red()
+                            .bold();
+
+                            eprintln!("{message}");
+                        }
+                    }
+                }
+
+                break ExitReason::StreamEnded;
+            }
+        }
+    };
 
     if let LogSource::Process(mut adb_child) = log_source {
         let kill_fail_message = format!("Failed to kill child process {}", adb_child.id())
@@ -1714,4 +3191,33 @@ fn main() {
         adb_child.kill().unwrap_or_panic(&kill_fail_message);
         adb_child.wait().unwrap_or_panic(&wait_fail_message);
     }
+
+    for writer in writers
+        .iter_mut()
+        .chain(raw_writer.as_mut())
+        .chain(session_writer.as_mut())
+    {
+        writer.finish();
+    }
+
+    let exit_reason = match wrapped_command {
+        Some(wrapped_command) => stop_wrapped_command(wrapped_command, exit_reason),
+        None => exit_reason,
+    };
+
+    let exit_code = exit_reason.exit_code(args.until.is_some());
+
+    if exit_reason != ExitReason::StreamEnded {
+        let bin_name = env!("CARGO_BIN_NAME").cyan().bold();
+        let message = format!(
+            "{}, exiting with code {exit_code}.",
+            exit_reason.description()
+        )
+        .cyan()
+        .bold();
+
+        status!("{bin_name} {message}");
+    }
+
+    exit(exit_code);
 }
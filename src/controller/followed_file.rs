@@ -0,0 +1,114 @@
+use std::fs::File;
+use std::fs::Metadata;
+use std::fs::metadata;
+
+use std::io::Read;
+use std::io::Result;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use std::thread::sleep;
+
+use std::time::Duration;
+
+/// How often the end of a followed file is checked for new lines, truncation or rotation.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Log file read like `tail -F`, reads wait at the end of the file for more lines instead
+/// of ending the stream.
+///
+/// The file is read again from its start when it is truncated, and reopened once it is
+/// replaced at `path`, e.g. rotated away and created again. The old file is read to its
+/// end first so that its last lines are not lost. A file that does not exist yet is
+/// waited for.
+#[derive(Debug)]
+pub struct FollowedFile {
+    path: PathBuf,
+    file: Option<File>,
+    position: u64,
+}
+
+impl FollowedFile {
+    pub fn open(path: &str) -> Self {
+        Self {
+            path: PathBuf::from(path),
+            file: File::open(path).ok(),
+            position: 0,
+        }
+    }
+
+    /// Returns whether `path` now refers to another file than `file`.
+    fn is_replaced(path: &Path, file: &File) -> bool {
+        match (file.metadata(), metadata(path)) {
+            (Ok(file_metadata), Ok(path_metadata)) => {
+                get_file_id(&file_metadata) != get_file_id(&path_metadata)
+            }
+            // Removed, the next file created at path is waited for
+            (Ok(_), Err(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn get_file_id(metadata: &Metadata) -> (u64, u64) {
+    use std::os::unix::fs::MetadataExt;
+
+    (metadata.dev(), metadata.ino())
+}
+
+/// Windows has no stable file index, the creation time tells a recreated file apart.
+#[cfg(windows)]
+fn get_file_id(metadata: &Metadata) -> (u64, u64) {
+    use std::os::windows::fs::MetadataExt;
+
+    (metadata.creation_time(), 0)
+}
+
+impl Read for FollowedFile {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        loop {
+            let Some(file) = self.file.as_mut() else {
+                match File::open(&self.path) {
+                    Ok(file) => {
+                        self.file = Some(file);
+                        self.position = 0;
+
+                        continue;
+                    }
+                    Err(_) => {
+                        sleep(FOLLOW_POLL_INTERVAL);
+
+                        continue;
+                    }
+                }
+            };
+
+            let bytes_read = file.read(buffer)?;
+
+            if bytes_read > 0 {
+                self.position += bytes_read as u64;
+
+                return Ok(bytes_read);
+            }
+
+            if file.metadata()?.len() < self.position {
+                file.seek(SeekFrom::Start(0))?;
+                self.position = 0;
+
+                continue;
+            }
+
+            if Self::is_replaced(&self.path, file) {
+                self.file = None;
+
+                continue;
+            }
+
+            sleep(FOLLOW_POLL_INTERVAL);
+        }
+    }
+}
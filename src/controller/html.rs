@@ -0,0 +1,251 @@
+use colored::Color;
+
+use crate::ColorDepth;
+use crate::LogRecord;
+use crate::RecordStyle;
+
+use crate::model::color_depth::ANSI_PALETTE;
+
+/// Opening of the HTML document, with the styles and the filtering script inlined
+/// so the exported file works offline.
+pub const HTML_HEADER: &str = include_str!("../../assets/html/header.html");
+
+/// Closing of the HTML document.
+pub const HTML_FOOTER: &str = "</div>\n</body>\n</html>\n";
+
+/// Renders a record as an HTML element of the exported log.
+pub fn get_html_record(record: &LogRecord, style: &RecordStyle) -> String {
+    match record {
+        LogRecord::Log {
+            timestamp,
+            pid,
+            tid,
+            package,
+            tag,
+            level,
+            source,
+            ..
+        } => {
+            let mut columns = Vec::default();
+
+            if let Some(source) = source {
+                columns.push(format!(
+                    r#"<span class="source">{}</span>"#,
+                    escape_html(source)
+                ));
+            }
+
+            if let Some(timestamp) = timestamp {
+                columns.push(format!(
+                    r#"<span class="time">{}</span>"#,
+                    escape_html(timestamp)
+                ));
+            }
+
+            columns.push(format!(r#"<span class="pid">{}</span>"#, escape_html(pid)));
+
+            if let Some(tid) = tid {
+                columns.push(format!(r#"<span class="tid">{}</span>"#, escape_html(tid)));
+            }
+
+            if let Some(package) = package {
+                columns.push(format!(
+                    r#"<span class="pkg">{}</span>"#,
+                    escape_html(package)
+                ));
+            }
+
+            columns.push(format!(
+                r#"<span class="tag" style="color:{}">{}</span>"#,
+                get_css_color(style.token_color),
+                escape_html(tag)
+            ));
+            columns.push(format!(
+                r#"<span class="level" style="color:{};background:{}">{level}</span>"#,
+                get_css_color(style.level_foreground),
+                get_css_color(style.level_background),
+            ));
+            columns.push(format!(
+                r#"<span class="msg">{}</span>"#,
+                get_html_from_ansi(&style.message)
+            ));
+
+            format!(
+                r#"<div class="line" data-level="{level}" data-tag="{}" data-pid="{}">{}</div>"#,
+                escape_html(tag),
+                escape_html(pid),
+                columns.concat()
+            ) + "\n"
+        }
+
+        LogRecord::ProcessStart {
+            pid,
+            uid,
+            gids,
+            package,
+            target,
+            ..
+        } => {
+            format!(
+                r#"<div class="banner" style="border-color:{}">Process <b style="color:{}">{}</b> created for <b>{}</b><br>PID: <b>{}</b> UID: <b>{}</b> GIDs: <b>{}</b></div>"#,
+                get_css_color(style.level_background),
+                get_css_color(style.token_color),
+                escape_html(package),
+                escape_html(target.as_deref().unwrap_or_default()),
+                escape_html(pid),
+                escape_html(uid.as_deref().unwrap_or_default()),
+                escape_html(gids.as_deref().unwrap_or_default()),
+            ) + "\n"
+        }
+
+        LogRecord::ProcessDeath { pid, package, .. } => {
+            format!(
+                r#"<div class="banner" style="border-color:{}">Process <b style="color:{}">{}</b> (PID: <b>{}</b>) ended</div>"#,
+                get_css_color(style.level_background),
+                get_css_color(style.token_color),
+                escape_html(package),
+                escape_html(pid),
+            ) + "\n"
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+fn get_css_color(color: Color) -> String {
+    let (r, g, b) = ColorDepth::get_rgb(color);
+
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Converts the SGR escape codes of a message to inline styled `<span>`s.
+fn get_html_from_ansi(text: &str) -> String {
+    let mut html = String::default();
+    let mut chars = text.chars().peekable();
+    let mut foreground: Option<(u8, u8, u8)> = None;
+    let mut background: Option<(u8, u8, u8)> = None;
+    let mut is_bold = false;
+    let mut is_span_open = false;
+    let mut plain = String::default();
+
+    while let Some(char) = chars.next() {
+        if char != '\x1b' || chars.peek() != Some(&'[') {
+            plain.push(char);
+            continue;
+        }
+
+        chars.next();
+
+        let mut code = String::default();
+        for next_char in chars.by_ref() {
+            if next_char.is_ascii_alphabetic() {
+                code.push(next_char);
+                break;
+            }
+            code.push(next_char);
+        }
+
+        // Only SGR (color) sequences are rendered, anything else is dropped
+        let Some(params) = code.strip_suffix('m') else {
+            continue;
+        };
+
+        html.push_str(&escape_html(&plain));
+        plain.clear();
+
+        let params = params
+            .split(';')
+            .map(|param| param.parse::<u8>().unwrap_or(0))
+            .collect::<Vec<_>>();
+        let mut index = 0;
+
+        while index < params.len() {
+            match params[index] {
+                0 => {
+                    foreground = None;
+                    background = None;
+                    is_bold = false;
+                }
+                1 => is_bold = true,
+                22 => is_bold = false,
+                39 => foreground = None,
+                49 => background = None,
+                sgr @ 30..=37 => foreground = Some(ANSI_PALETTE[(sgr - 30) as usize]),
+                sgr @ 90..=97 => foreground = Some(ANSI_PALETTE[(sgr - 90 + 8) as usize]),
+                sgr @ 40..=47 => background = Some(ANSI_PALETTE[(sgr - 40) as usize]),
+                sgr @ 100..=107 => background = Some(ANSI_PALETTE[(sgr - 100 + 8) as usize]),
+                sgr @ (38 | 48) => {
+                    let color = match params.get(index + 1) {
+                        Some(5) => {
+                            index += 2;
+                            params
+                                .get(index)
+                                .map(|&code| ColorDepth::get_ansi_256_rgb(code))
+                        }
+                        Some(2) if index + 4 < params.len() => {
+                            index += 4;
+                            Some((params[index - 2], params[index - 1], params[index]))
+                        }
+                        _ => None,
+                    };
+
+                    if sgr == 38 {
+                        foreground = color;
+                    } else {
+                        background = color;
+                    }
+                }
+                _ => {}
+            }
+
+            index += 1;
+        }
+
+        if is_span_open {
+            html.push_str("</span>");
+            is_span_open = false;
+        }
+
+        let mut css = Vec::default();
+
+        if let Some((r, g, b)) = foreground {
+            css.push(format!("color:#{r:02x}{g:02x}{b:02x}"));
+        }
+
+        if let Some((r, g, b)) = background {
+            css.push(format!("background:#{r:02x}{g:02x}{b:02x}"));
+        }
+
+        if is_bold {
+            css.push("font-weight:bold".to_string());
+        }
+
+        if !css.is_empty() {
+            html.push_str(&format!(r#"<span style="{}">"#, css.join(";")));
+            is_span_open = true;
+        }
+    }
+
+    html.push_str(&escape_html(&plain));
+
+    if is_span_open {
+        html.push_str("</span>");
+    }
+
+    html
+}
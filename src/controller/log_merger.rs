@@ -0,0 +1,103 @@
+use chrono::NaiveDateTime;
+
+use std::cmp::Reverse;
+
+use std::collections::BinaryHeap;
+
+use std::io::BufRead;
+use std::io::Result;
+
+use crate::LogEntry;
+
+/// Interleaves the lines of several logs chronologically, with a k-way merge on the
+/// timestamps of their lines.
+///
+/// Each log is expected to be in order already, as logcat captures are. Lines without a
+/// timestamp, e.g. `--------- beginning of main`, keep the timestamp of the line before
+/// them so they stay in place. Lines with the same timestamp come in the order the logs
+/// were given, then in their order within their log.
+///
+/// Yields the index of the log each line was read from along with the line.
+///
+/// ### Example
+///
+/// ```
+/// use pidcat::LogMerger;
+///
+/// let device = "10-18 12:00:00.000  1  1 I A: one\n10-18 12:00:02.000  1  1 I A: four\n";
+/// let emulator = "10-18 12:00:01.000  2  2 I B: two\n10-18 12:00:01.000  2  2 I B: three\n";
+///
+/// let merger = LogMerger::new(vec![Box::new(device.as_bytes()), Box::new(emulator.as_bytes())]);
+/// let lines = merger.unwrap().map(|line| line.unwrap()).collect::<Vec<_>>();
+///
+/// assert_eq!(lines[1], (1, "10-18 12:00:01.000  2  2 I B: two".to_string()));
+/// assert_eq!(lines[2].0, 1);
+/// assert_eq!(lines[3], (0, "10-18 12:00:02.000  1  1 I A: four".to_string()));
+/// ```
+pub struct LogMerger {
+    readers: Vec<Box<dyn BufRead + Send>>,
+    /// Next line of each log, `None` once the log ended
+    next_lines: Vec<Option<String>>,
+    /// Timestamp of the last timestamped line of each log
+    last_times: Vec<Option<NaiveDateTime>>,
+    heap: BinaryHeap<Reverse<(NaiveDateTime, usize)>>,
+}
+
+impl LogMerger {
+    pub fn new(readers: Vec<Box<dyn BufRead + Send>>) -> Result<Self> {
+        let mut merger = Self {
+            next_lines: vec![None; readers.len()],
+            last_times: vec![None; readers.len()],
+            heap: BinaryHeap::with_capacity(readers.len()),
+            readers,
+        };
+
+        for index in 0..merger.readers.len() {
+            merger.read_next_line(index)?;
+        }
+
+        Ok(merger)
+    }
+
+    /// Reads the next line of a log and queues it by its timestamp.
+    fn read_next_line(&mut self, index: usize) -> Result<()> {
+        let buffer = &mut vec![];
+
+        if self.readers[index].read_until(b'\n', buffer)? == 0 {
+            self.next_lines[index] = None;
+
+            return Ok(());
+        }
+
+        let line = String::from_utf8_lossy(buffer)
+            .trim_end_matches(['\r', '\n'])
+            .to_string();
+
+        let previous = self.last_times[index];
+
+        if let Some(time) = LogEntry::parse(&line).and_then(|entry| entry.get_time_after(previous))
+        {
+            self.last_times[index] = Some(time);
+        }
+
+        let time = self.last_times[index].unwrap_or(NaiveDateTime::MIN);
+        self.heap.push(Reverse((time, index)));
+        self.next_lines[index] = Some(line);
+
+        Ok(())
+    }
+}
+
+impl Iterator for LogMerger {
+    type Item = Result<(usize, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((_, index)) = self.heap.pop()?;
+        let line = self.next_lines[index].take()?;
+
+        match self.read_next_line(index) {
+            Ok(()) => Some(Ok((index, line))),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
@@ -1 +1,6 @@
+pub mod followed_file;
+pub mod html;
+pub mod log_merger;
+pub mod output_file;
+pub mod rotating_file;
 pub mod writer;
@@ -0,0 +1,89 @@
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+use std::fs::File;
+
+use std::io::BufWriter;
+use std::io::Result;
+use std::io::Write;
+
+use xz2::write::XzEncoder;
+
+use zstd::stream::write::Encoder as ZstdEncoder;
+
+use crate::CompressionFormat;
+
+/// Default xz preset, same as the `xz` command line tool.
+const XZ_PRESET: u32 = 6;
+
+/// Output file, compressed on the fly when its path ends with `.gz`, `.zst` or `.xz`.
+pub enum OutputFile {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<File>),
+    Zstd(ZstdEncoder<'static, File>),
+    Xz(XzEncoder<File>),
+}
+
+impl OutputFile {
+    pub fn create(path: &str) -> Result<Self> {
+        let file = File::create(path)?;
+
+        Ok(match CompressionFormat::from_path(path) {
+            CompressionFormat::None => Self::Plain(BufWriter::new(file)),
+            CompressionFormat::Gzip => Self::Gzip(GzEncoder::new(file, Compression::default())),
+            CompressionFormat::Zstd => Self::Zstd(ZstdEncoder::new(file, 0)?),
+            CompressionFormat::Xz => Self::Xz(XzEncoder::new(file, XZ_PRESET)),
+        })
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        !matches!(self, Self::Plain(_))
+    }
+
+    /// Writes the end of the compressed stream, the file must not be written to afterwards.
+    pub fn finish(&mut self) -> Result<()> {
+        match self {
+            Self::Plain(file) => file.flush(),
+            Self::Gzip(encoder) => encoder.try_finish(),
+            Self::Zstd(encoder) => encoder.do_finish(),
+            Self::Xz(encoder) => encoder.try_finish(),
+        }
+    }
+}
+
+impl Debug for OutputFile {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Plain(file) => write!(formatter, "{:?}", file.get_ref()),
+            Self::Gzip(encoder) => write!(formatter, "Gzip({:?})", encoder.get_ref()),
+            Self::Zstd(encoder) => write!(formatter, "Zstd({:?})", encoder.get_ref()),
+            Self::Xz(encoder) => write!(formatter, "Xz({:?})", encoder.get_ref()),
+        }
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        match self {
+            Self::Plain(file) => file.write(buffer),
+            Self::Gzip(encoder) => encoder.write(buffer),
+            Self::Zstd(encoder) => encoder.write(buffer),
+            Self::Xz(encoder) => encoder.write(buffer),
+        }
+    }
+
+    /// Flushing an encoder ends its current block, which ruins the compression ratio when
+    /// done for every line, so compressed files only flush what the encoder already emitted.
+    fn flush(&mut self) -> Result<()> {
+        match self {
+            Self::Plain(file) => file.flush(),
+            Self::Gzip(encoder) => encoder.get_mut().flush(),
+            Self::Zstd(encoder) => encoder.get_mut().flush(),
+            Self::Xz(encoder) => encoder.get_mut().flush(),
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use chrono::Local;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+use once_cell::sync::Lazy;
+
+use regex::Regex;
+
+use std::fs::File;
+use std::fs::read_dir;
+use std::fs::remove_file;
+use std::fs::rename;
+
+use std::io::Error;
+use std::io::Result;
+use std::io::Write;
+use std::io::copy;
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use std::sync::mpsc::Sender;
+use std::sync::mpsc::channel;
+
+use std::thread::JoinHandle;
+use std::thread::spawn;
+
+use std::time::Instant;
+
+use crate::CompressionFormat;
+use crate::Rotation;
+use crate::ValueOrPanic;
+
+use crate::controller::output_file::OutputFile;
+
+/// Timestamp and collision counter that [RotatingFile] appends to the rotated segment names.
+static SEGMENT_SUFFIX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^-(\d{8}-\d{6})(?:-(\d+))?$").unwrap_or_panic("Invalid Regex for SEGMENT_SUFFIX")
+});
+
+/// Output file that is rolled over according to a [Rotation] policy.
+///
+/// The active segment always lives at `path`, rotated segments are renamed next to it
+/// with a timestamp, e.g. `soak.log` becomes `soak-20261018-231500.log`.
+///
+/// `file` is only `None` while a segment is being rotated, since Windows can not rename
+/// a file that is still open.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    file: Option<OutputFile>,
+    rotation: Rotation,
+    written: u64,
+    opened_at: Instant,
+    /// Queues rotated segments for the worker that compresses them and prunes old ones
+    segment_sender: Option<Sender<PathBuf>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl RotatingFile {
+    pub fn create(path: &str, rotation: Rotation) -> Result<Self> {
+        Ok(Self {
+            path: PathBuf::from(path),
+            file: Some(OutputFile::create(path)?),
+            rotation,
+            written: 0,
+            opened_at: Instant::now(),
+            segment_sender: None,
+            worker: None,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns whether the active segment reached the size or age limit.
+    pub fn needs_rotation(&self) -> bool {
+        let size_exceeded = self.rotation.size.is_some_and(|size| self.written >= size);
+        let interval_exceeded = self
+            .rotation
+            .interval
+            .is_some_and(|interval| self.opened_at.elapsed() >= interval);
+
+        size_exceeded || interval_exceeded
+    }
+
+    /// Renames the active segment to a timestamped name and starts a new one at `path`.
+    ///
+    /// The rename is atomic, so readers of the rotated segment never see a partial file,
+    /// compression and pruning of old segments run in the background, one segment after
+    /// the other so a segment is never pruned while it is being compressed.
+    pub fn rotate(&mut self) -> Result<()> {
+        let mut file = self.file.take().ok_or(Error::other("File is closed"))?;
+        let is_compressed = file.is_compressed();
+
+        file.finish()?;
+        drop(file);
+
+        let rotated_path = self.get_rotated_path();
+        rename(&self.path, &rotated_path)?;
+
+        let path_str = self.path.to_string_lossy().to_string();
+        self.file = Some(OutputFile::create(&path_str)?);
+        self.written = 0;
+        self.opened_at = Instant::now();
+
+        if self.segment_sender.is_none() {
+            let (segment_sender, segment_receiver) = channel::<PathBuf>();
+            let path = self.path.clone();
+            let rotation = self.rotation.clone();
+
+            self.segment_sender = Some(segment_sender);
+            self.worker = Some(spawn(move || {
+                for rotated_path in segment_receiver {
+                    // Segments queued behind a long compression may already be pruned
+                    if rotation.compress && !is_compressed && rotated_path.exists() {
+                        compress_segment(&rotated_path);
+                    }
+
+                    if let Some(keep) = rotation.keep {
+                        prune_segments(&path, keep);
+                    }
+                }
+            }));
+        }
+
+        if let Some(segment_sender) = &self.segment_sender {
+            segment_sender
+                .send(rotated_path)
+                .map_err(|_| Error::other("Segment worker stopped"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Ends the active segment and waits for the background compression of rotated
+    /// segments to complete.
+    pub fn finish(&mut self) -> Result<()> {
+        // Closing the queue lets the worker exit once the queued segments are done
+        self.segment_sender = None;
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+
+        match self.file.as_mut() {
+            Some(file) => file.finish(),
+            None => Ok(()),
+        }
+    }
+
+    fn get_file(&mut self) -> Result<&mut OutputFile> {
+        self.file.as_mut().ok_or(Error::other("File is closed"))
+    }
+
+    fn get_rotated_path(&self) -> PathBuf {
+        let (stem, extension) = get_name_parts(&self.path);
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S");
+
+        let mut counter = 0;
+        loop {
+            let suffix = match counter {
+                0 => format!("-{timestamp}"),
+                _ => format!("-{timestamp}-{counter}"),
+            };
+            let rotated_path = self
+                .path
+                .with_file_name(format!("{stem}{suffix}{extension}"));
+            let compressed_path = append_extension(&rotated_path, "gz");
+
+            if !rotated_path.exists() && !compressed_path.exists() {
+                return rotated_path;
+            }
+
+            counter += 1;
+        }
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        self.get_file()?.write_all(buffer)?;
+        self.written += buffer.len() as u64;
+
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.get_file()?.flush()
+    }
+}
+
+/// Splits `soak.log` into `("soak", ".log")`, the extension is empty if there is none.
+///
+/// A compression extension is kept together with the one before it, e.g. `soak.log.gz`
+/// is split into `("soak", ".log.gz")`.
+fn get_name_parts(path: &Path) -> (String, String) {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let extensions_count = match CompressionFormat::from_path(&name) {
+        CompressionFormat::None => 1,
+        _ => 2,
+    };
+
+    let mut split_at = name.len();
+    for _ in 0..extensions_count {
+        match name[..split_at].rfind('.') {
+            Some(index) if index > 0 => split_at = index,
+            _ => break,
+        }
+    }
+
+    (name[..split_at].to_string(), name[split_at..].to_string())
+}
+
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+
+    PathBuf::from(path)
+}
+
+/// Gzips a rotated segment into `<segment>.gz` and removes the uncompressed segment.
+fn compress_segment(path: &Path) {
+    let compressed_path = append_extension(path, "gz");
+    let temp_path = append_extension(&compressed_path, "tmp");
+
+    let err_msg = format!("Failed to compress {}", path.display());
+    let mut source = File::open(path).unwrap_or_panic(&err_msg);
+    let mut encoder = GzEncoder::new(
+        File::create(&temp_path).unwrap_or_panic(&err_msg),
+        Compression::default(),
+    );
+
+    copy(&mut source, &mut encoder).unwrap_or_panic(&err_msg);
+    encoder.finish().unwrap_or_panic(&err_msg);
+
+    rename(&temp_path, &compressed_path).unwrap_or_panic(&err_msg);
+    remove_file(path).unwrap_or_panic(&err_msg);
+}
+
+/// Deletes the oldest rotated segments of `path`, keeping the newest `keep` ones.
+fn prune_segments(path: &Path, keep: usize) {
+    let (stem, extension) = get_name_parts(path);
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let Ok(entries) = read_dir(&directory) else {
+        return;
+    };
+
+    let mut segments = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let name_rest = name.strip_prefix(&stem)?;
+            let suffix = name_rest.strip_suffix(&extension).or_else(|| {
+                name_rest
+                    .strip_suffix(".gz")
+                    .and_then(|name_rest| name_rest.strip_suffix(&extension))
+            })?;
+
+            let caps = SEGMENT_SUFFIX.captures(suffix)?;
+            let timestamp = caps[1].to_string();
+            let counter = caps
+                .get(2)
+                .and_then(|mat| mat.as_str().parse::<usize>().ok())
+                .unwrap_or(0);
+
+            Some(((timestamp, counter), entry.path()))
+        })
+        .collect::<Vec<_>>();
+
+    segments.sort_by(|(first, _), (second, _)| second.cmp(first));
+
+    for (_, segment_path) in segments.into_iter().skip(keep) {
+        let _ = remove_file(segment_path);
+    }
+}
@@ -2,26 +2,39 @@ use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 
-use std::fs::File;
-
+use std::io::BufWriter;
 use std::io::Result;
 use std::io::Stdout;
 use std::io::Write;
 use std::io::stdout;
 
+use crate::LogRecord;
+use crate::LogcatMessage;
+use crate::LogcatMetadata;
+use crate::OutputFormat;
+use crate::RecordStyle;
+use crate::Rotation;
 use crate::ValueOrPanic;
 
+use crate::controller::html::HTML_FOOTER;
+use crate::controller::html::HTML_HEADER;
+use crate::controller::html::get_html_record;
+use crate::controller::output_file::OutputFile;
+use crate::controller::rotating_file::RotatingFile;
+
 #[derive(Debug)]
 enum WriterTarget {
-    Console(Stdout),
-    File(File),
+    Console(BufWriter<Stdout>),
+    File(OutputFile),
+    RotatingFile(RotatingFile),
 }
 
 impl Display for WriterTarget {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
         match self {
-            Self::Console(stdout) => write!(formatter, "{stdout:?}"),
+            Self::Console(stdout) => write!(formatter, "{:?}", stdout.get_ref()),
             Self::File(file) => write!(formatter, "{file:?}"),
+            Self::RotatingFile(file) => write!(formatter, "{}", file.path().display()),
         }
     }
 }
@@ -31,6 +44,7 @@ impl Write for WriterTarget {
         match self {
             Self::Console(stdout) => stdout.write_all(buffer).map(|_| buffer.len()),
             Self::File(file) => file.write_all(buffer).map(|_| buffer.len()),
+            Self::RotatingFile(file) => file.write_all(buffer).map(|_| buffer.len()),
         }
     }
 
@@ -38,6 +52,7 @@ impl Write for WriterTarget {
         match self {
             Self::Console(stdout) => stdout.flush(),
             Self::File(file) => file.flush(),
+            Self::RotatingFile(file) => file.flush(),
         }
     }
 }
@@ -46,31 +61,173 @@ impl Write for WriterTarget {
 pub struct Writer {
     pub width: i16,
     pub show_colors: bool,
+    /// Wraps long messages between words instead of at the console edge
+    pub word_wrap: bool,
+    pub format: OutputFormat,
+    /// Capture details written at the end of [OutputFormat::Logcat] documents
+    pub logcat_metadata: LogcatMetadata,
+    /// Records written to the current document, to separate them in JSON arrays
+    records_written: usize,
     target: WriterTarget,
 }
 
 impl Writer {
-    pub fn new_console(width: i16, show_colors: bool) -> Self {
-        Self {
+    pub fn new_console(
+        width: i16,
+        show_colors: bool,
+        word_wrap: bool,
+        format: OutputFormat,
+    ) -> Self {
+        let mut writer = Self {
             width,
             show_colors,
-            target: WriterTarget::Console(stdout()),
-        }
+            word_wrap,
+            format,
+            logcat_metadata: LogcatMetadata::default(),
+            records_written: 0,
+            target: WriterTarget::Console(BufWriter::new(stdout())),
+        };
+
+        writer.write_header();
+        writer
     }
 
-    pub fn new_file(file: File) -> Self {
-        Self {
+    /// Creates a file writer, compressed according to the extension of `path`.
+    pub fn new_file(path: &str, format: OutputFormat) -> Self {
+        let file = OutputFile::create(path).unwrap_or_panic("Failed to create output file");
+        let mut writer = Self {
             width: -1,
             show_colors: false,
+            word_wrap: false,
+            format,
+            logcat_metadata: LogcatMetadata::default(),
+            records_written: 0,
             target: WriterTarget::File(file),
+        };
+
+        writer.write_header();
+        writer
+    }
+
+    /// Creates a file writer that rolls `path` over according to `rotation`.
+    pub fn new_rotating_file(path: &str, format: OutputFormat, rotation: Rotation) -> Self {
+        let file =
+            RotatingFile::create(path, rotation).unwrap_or_panic("Failed to create output file");
+        let mut writer = Self {
+            width: -1,
+            show_colors: false,
+            word_wrap: false,
+            format,
+            logcat_metadata: LogcatMetadata::default(),
+            records_written: 0,
+            target: WriterTarget::RotatingFile(file),
+        };
+
+        writer.write_header();
+        writer
+    }
+
+    fn write_header(&mut self) {
+        self.records_written = 0;
+
+        match self.format {
+            OutputFormat::Html => self.write(HTML_HEADER),
+            OutputFormat::Logcat => self.write("{\n  \"logcatMessages\": [\n"),
+            _ => {}
         }
     }
 
+    /// Writes `text` to the buffered target, which is only guaranteed to reach it after
+    /// [Writer::flush].
     pub fn write(&mut self, text: &str) {
         let err_msg = format!("Failed to write to {}", self.target);
         self.target.write(text.as_bytes()).unwrap_or_panic(&err_msg);
     }
 
+    /// Writes a structured record, ignored by [OutputFormat::Text] writers which
+    /// get the formatted line through [Writer::write] instead.
+    pub fn write_record(&mut self, record: &LogRecord, style: &RecordStyle) {
+        match self.format {
+            OutputFormat::Text => {}
+            OutputFormat::Jsonl => {
+                let json =
+                    serde_json::to_string(record).unwrap_or_panic("Failed to serialize log record");
+
+                self.write(&json);
+                self.write("\n");
+            }
+            OutputFormat::Html => self.write(&get_html_record(record, style)),
+            OutputFormat::Logcat => {
+                let Some(message) = LogcatMessage::from_record(record) else {
+                    return;
+                };
+                let json = serde_json::to_string(&message)
+                    .unwrap_or_panic("Failed to serialize log record");
+
+                if self.records_written > 0 {
+                    self.write(",\n");
+                }
+
+                self.write("    ");
+                self.write(&json);
+            }
+        }
+
+        self.records_written += 1;
+    }
+
+    /// Rotates the output file once it is due, must only be called between log entries
+    /// so that every segment holds complete lines.
+    ///
+    /// Each segment is a complete document of its own, e.g. HTML segments get their own
+    /// header and footer.
+    pub fn rotate_if_needed(&mut self) {
+        let WriterTarget::RotatingFile(file) = &self.target else {
+            return;
+        };
+
+        if !file.needs_rotation() {
+            return;
+        }
+
+        self.write_footer();
+
+        if let WriterTarget::RotatingFile(file) = &mut self.target {
+            let err_msg = format!("Failed to rotate {}", file.path().display());
+            file.rotate().unwrap_or_panic(&err_msg);
+        }
+
+        self.write_header();
+    }
+
+    /// Writes whatever the format needs to close the output, e.g. the end of the HTML document.
+    pub fn finish(&mut self) {
+        self.write_footer();
+        self.flush();
+
+        let err_msg = format!("Failed to finish {}", self.target);
+        let result = match &mut self.target {
+            WriterTarget::Console(_) => Ok(()),
+            WriterTarget::File(file) => file.finish(),
+            WriterTarget::RotatingFile(file) => file.finish(),
+        };
+
+        result.unwrap_or_panic(&err_msg);
+    }
+
+    fn write_footer(&mut self) {
+        match self.format {
+            OutputFormat::Html => self.write(HTML_FOOTER),
+            OutputFormat::Logcat => {
+                let json = serde_json::to_string(&self.logcat_metadata)
+                    .unwrap_or_panic("Failed to serialize logcat metadata");
+
+                self.write(&format!("\n  ],\n  \"metadata\": {json}\n}}\n"));
+            }
+            _ => {}
+        }
+    }
+
     pub fn flush(&mut self) {
         let err_msg = format!("Failed to flush {}", self.target);
         self.target.flush().unwrap_or_panic(&err_msg);
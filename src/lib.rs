@@ -4,10 +4,43 @@ mod model;
 pub use model::adb_device::AdbDevice;
 pub use model::adb_state::AdbState;
 pub use model::ansi_segment::AnsiSegment;
+pub use model::builtin_theme::BuiltinTheme;
 pub use model::cli_args::CliArgs;
+pub use model::color_assignment::ColorAssignment;
+pub use model::color_depth::ColorDepth;
+pub use model::color_mode::ColorMode;
+pub use model::compression_format::CompressionFormat;
+pub use model::exit_reason::ExitReason;
+pub use model::fail_on::FailOn;
+pub use model::highlight::Highlight;
+pub use model::layout::Alignment;
+pub use model::layout::Layout;
+pub use model::layout::LayoutColumn;
+pub use model::layout::LayoutField;
+pub use model::layout::LayoutItem;
+pub use model::layout::Truncation;
+pub use model::line_token::LineToken;
+pub use model::log_entry::LogEntry;
+pub use model::log_event::LogEvent;
 pub use model::log_level::LogLevel;
+pub use model::log_record::LogRecord;
+pub use model::output_format::OutputFormat;
+pub use model::record_style::RecordStyle;
+pub use model::rotation::Rotation;
+pub use model::session_record::SESSION_VERSION;
+pub use model::session_record::SessionRecord;
 pub use model::state::State;
+pub use model::system_tags_profile::SystemTagsProfile;
+pub use model::theme::Theme;
+pub use model::time_format::TimeFormat;
 pub use model::log_source::LogSource;
+pub use model::logcat_file::LogcatFile;
+pub use model::logcat_file::LogcatHeader;
+pub use model::logcat_file::LogcatMessage;
+pub use model::logcat_file::LogcatMetadata;
+pub use model::logcat_file::LogcatTimestamp;
 pub use model::value_unwrap::ValueOrPanic;
 
+pub use controller::followed_file::FollowedFile;
+pub use controller::log_merger::LogMerger;
 pub use controller::writer::Writer;
@@ -0,0 +1,68 @@
+use clap::ValueEnum;
+
+use clap::builder::PossibleValue;
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+static DARK_THEME: &str = include_str!("../../assets/themes/dark.txt");
+static LIGHT_THEME: &str = include_str!("../../assets/themes/light.txt");
+static HIGH_CONTRAST_THEME: &str = include_str!("../../assets/themes/high-contrast.txt");
+static COLORBLIND_THEME: &str = include_str!("../../assets/themes/colorblind.txt");
+
+/// Theme bundled with PidCat, selected with `--theme`.
+///
+/// Theme files given with `--theme-file` are applied on top of it.
+#[derive(Eq, Copy, Debug, Clone, PartialEq, Default)]
+pub enum BuiltinTheme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+    Colorblind,
+}
+
+impl BuiltinTheme {
+    /// Returns the content of the theme file.
+    pub fn content(&self) -> &'static str {
+        match self {
+            Self::Dark => DARK_THEME,
+            Self::Light => LIGHT_THEME,
+            Self::HighContrast => HIGH_CONTRAST_THEME,
+            Self::Colorblind => COLORBLIND_THEME,
+        }
+    }
+}
+
+impl Display for BuiltinTheme {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        let name = match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+            Self::HighContrast => "high-contrast",
+            Self::Colorblind => "colorblind",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+impl ValueEnum for BuiltinTheme {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::Dark,
+            Self::Light,
+            Self::HighContrast,
+            Self::Colorblind,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Dark => PossibleValue::new("dark"),
+            Self::Light => PossibleValue::new("light"),
+            Self::HighContrast => PossibleValue::new("high-contrast").alias("contrast"),
+            Self::Colorblind => PossibleValue::new("colorblind").alias("okabe-ito"),
+        })
+    }
+}
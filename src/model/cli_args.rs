@@ -7,16 +7,41 @@ use clap::builder::styling::Styles;
 
 use colored::Colorize;
 
+use itertools::Itertools;
+
+use is_terminal::IsTerminal;
+
+use regex::Regex;
+
+use std::io::stdout;
+
+use std::path::Path;
+
+use std::time::Duration;
+
+use crate::BuiltinTheme;
+use crate::ColorAssignment;
+use crate::ColorDepth;
+use crate::ColorMode;
+use crate::FailOn;
+use crate::Highlight;
+use crate::Layout;
 use crate::LogLevel;
+use crate::OutputFormat;
+use crate::Rotation;
+use crate::SystemTagsProfile;
+use crate::TimeFormat;
 use crate::ValueOrPanic;
 
 const POSITIONAL_ARGUMENTS: &str = "Positional Arguments";
 const ABOUT_OPTIONS: &str = "Options";
 const DEVICE_OPTIONS: &str = "Device Options";
+const INPUT_OPTIONS: &str = "Input Options";
 const FILTERING_OPTIONS: &str = "Filtering Options";
 const FORMATTING_OPTIONS: &str = "Formatting Options";
 const COLORING_OPTIONS: &str = "Color Options";
 const OUTPUT_OPTIONS: &str = "Output Options";
+const STOP_OPTIONS: &str = "Stop Conditions";
 
 #[derive(Debug, Parser)]
 #[command(
@@ -36,12 +61,24 @@ pub struct CliArgs {
         value_name = "PACKAGE",
         help_heading = POSITIONAL_ARGUMENTS,
         help = concat!(
-            "Application package name(s)",
+            "Application package name(s) or glob pattern(s), e.g. com.example.*",
             "\nThis can be specified multiple times"
         ),
     )]
     pub packages: Vec<String>,
 
+    #[arg(
+        last = true,
+        required = false,
+        value_name = "COMMAND",
+        help_heading = POSITIONAL_ARGUMENTS,
+        help = concat!(
+            "Command to run after --, logs are captured until it exits",
+            "\nPidCat exits with the exit code of the command"
+        ),
+    )]
+    pub command: Vec<String>,
+
     #[arg(
         short = 'h',
         long = "help",
@@ -110,6 +147,88 @@ pub struct CliArgs {
     )]
     pub device_serial: Option<String>,
 
+    #[arg(
+        short = 'F',
+        long = "file",
+        required = false,
+        value_name = "FILE_PATH",
+        default_value = None,
+        help_heading = INPUT_OPTIONS,
+        help = concat!(
+            "Read logs from a capture file instead of ADB, .gz, .zst and .xz files are",
+            "\ndecompressed on the fly and .logcat Android Studio captures are converted.",
+            "\nThis can be specified multiple times"
+        ),
+    )]
+    pub files: Option<Vec<String>>,
+
+    #[arg(
+        long = "follow",
+        required = false,
+        value_name = None,
+        default_value_t = false,
+        requires = "files",
+        help_heading = INPUT_OPTIONS,
+        action = ArgAction::SetTrue,
+        help = concat!(
+            "Keep reading the --file paths as they grow, like tail -F",
+            "\nTruncated files are read again and rotated files are reopened"
+        ),
+    )]
+    pub follow: bool,
+
+    #[arg(
+        long = "replay",
+        required = false,
+        value_name = "SESSION_PATH",
+        default_value = None,
+        conflicts_with = "files",
+        help_heading = INPUT_OPTIONS,
+        help = "Replay a session saved with --record, with the original timing between lines",
+    )]
+    pub replay_path: Option<String>,
+
+    #[arg(
+        long = "speed",
+        required = false,
+        value_name = "SPEED",
+        default_value = "1x",
+        requires = "replay_path",
+        value_parser = CliArgs::parse_speed,
+        help_heading = INPUT_OPTIONS,
+        help = "Playback speed of --replay, e.g. 2x or 0.5x, max replays without pauses",
+    )]
+    pub speed: f64,
+
+    #[arg(
+        long = "listen",
+        required = false,
+        value_name = "[HOST:]PORT",
+        default_value = None,
+        conflicts_with_all = ["files", "replay_path", "connect_address"],
+        value_parser = CliArgs::parse_listen_address,
+        help_heading = INPUT_OPTIONS,
+        help = concat!(
+            "Read logcat text sent over TCP to PORT, e.g. by adb logcat | nc HOST PORT",
+            "\nA new connection is waited for whenever the sender disconnects"
+        ),
+    )]
+    pub listen_address: Option<String>,
+
+    #[arg(
+        long = "connect",
+        required = false,
+        value_name = "HOST:PORT",
+        default_value = None,
+        conflicts_with_all = ["files", "replay_path"],
+        help_heading = INPUT_OPTIONS,
+        help = concat!(
+            "Read logcat text from a TCP server, e.g. adb logcat | nc -lk PORT",
+            "\nThe connection is retried until the server is reachable again"
+        ),
+    )]
+    pub connect_address: Option<String>,
+
     #[arg(
         short = 'a',
         long = "all",
@@ -146,6 +265,19 @@ pub struct CliArgs {
     )]
     pub current_app: bool,
 
+    #[arg(
+        long = "ignore-package",
+        required = false,
+        default_value = None,
+        value_name = "IGNORED_PACKAGE",
+        help_heading = FILTERING_OPTIONS,
+        help = concat!(
+            "Filter output by ignoring specified package name(s) or glob pattern(s)",
+            "\nThis can be specified multiple times, or as a comma separated list"
+        ),
+    )]
+    pub ignore_package: Option<Vec<String>>,
+
     #[arg(
         short = 'I',
         long = "ignore-system-tags",
@@ -161,6 +293,33 @@ pub struct CliArgs {
     )]
     pub ignore_system_tags: bool,
 
+    #[arg(
+        long = "system-tags-profile",
+        ignore_case = true,
+        required = false,
+        value_name = "PROFILE",
+        default_value_t = SystemTagsProfile::Auto,
+        help_heading = FILTERING_OPTIONS,
+        help = concat!(
+            "Vendor profile of system tags ignored by --ignore-system-tags",
+            "\nauto selects the profile from the device manufacturer"
+        ),
+    )]
+    pub system_tags_profile: SystemTagsProfile,
+
+    #[arg(
+        long = "system-tags-file",
+        required = false,
+        default_value = None,
+        value_name = "FILE_PATH",
+        help_heading = FILTERING_OPTIONS,
+        help = concat!(
+            "Additional system tags ignored by --ignore-system-tags, one regex per line",
+            "\nThis can be specified multiple times"
+        ),
+    )]
+    pub system_tags_files: Option<Vec<String>>,
+
     #[arg(
         short = 't',
         long = "tag",
@@ -235,6 +394,49 @@ pub struct CliArgs {
     )]
     pub show_package: bool,
 
+    #[arg(
+        short = 'T',
+        required = false,
+        value_name = None,
+        long = "show-tid",
+        default_value_t = false,
+        help_heading = FORMATTING_OPTIONS,
+        action = ArgAction::SetTrue,
+        help = "Show thread id in output",
+    )]
+    pub show_tid: bool,
+
+    #[arg(
+        required = false,
+        value_name = None,
+        long = "thread-names",
+        default_value_t = false,
+        help_heading = FORMATTING_OPTIONS,
+        action = ArgAction::SetTrue,
+        help = concat!(
+            "Show thread names instead of thread ids, implies --show-tid",
+            "\nNames are read once per process with `adb shell ps -T`, from live devices only"
+        ),
+    )]
+    pub thread_names: bool,
+
+    #[arg(
+        long = "layout",
+        required = false,
+        value_name = "TEMPLATE",
+        default_value = None,
+        value_parser = Layout::parse,
+        help_heading = FORMATTING_OPTIONS,
+        help = concat!(
+            "Line layout, e.g. \"{time:12} {pkg:20<} {tid:5} {tag:>20} {level} {msg}\"",
+            "\nFields: time, pid, tid, pkg, tag, level, source and msg, which has to be last",
+            "\nSpec: optional alignment (<, >, ^) around an optional width, then",
+            "\nan optional truncation mode (e for an end ellipsis, m for a middle one)",
+            "\nColumns without a width use the matching width option"
+        ),
+    )]
+    pub layout: Option<Layout>,
+
     #[arg(
         short = 'S',
         required = false,
@@ -247,6 +449,36 @@ pub struct CliArgs {
     )]
     pub always_show_tags: bool,
 
+    #[arg(
+        long = "time",
+        ignore_case = true,
+        required = false,
+        value_name = "MODE",
+        default_value = None,
+        help_heading = FORMATTING_OPTIONS,
+        help = concat!(
+            "Show a time column",
+            "\nabsolute shows the device time of each line",
+            "\nrelative shows the time since the process started, or since the first line",
+            "\ndelta shows the time since the previous shown line"
+        ),
+    )]
+    pub time: Option<TimeFormat>,
+
+    #[arg(
+        required = false,
+        value_name = None,
+        long = "word-wrap",
+        default_value_t = false,
+        help_heading = FORMATTING_OPTIONS,
+        action = ArgAction::SetTrue,
+        help = concat!(
+            "Wrap long messages between words, at spaces and URL or path separators,",
+            "\ninstead of at the console edge, tokens longer than a line are still split"
+        ),
+    )]
+    pub word_wrap: bool,
+
     #[arg(
         short = 'x',
         required = false,
@@ -292,6 +524,34 @@ pub struct CliArgs {
     )]
     pub gc_color: bool,
 
+    #[arg(
+        long = "highlight",
+        required = false,
+        value_name = "REGEX[=COLOR]",
+        value_parser = Highlight::parse,
+        help_heading = COLORING_OPTIONS,
+        help = concat!(
+            "Highlight matches of REGEX in messages without filtering them",
+            "\nCapture groups are highlighted in their own colors",
+            "\nThis can be specified multiple times"
+        ),
+    )]
+    pub highlight: Vec<Highlight>,
+
+    #[arg(
+        long = "color",
+        required = false,
+        value_name = "WHEN",
+        default_value_t = ColorMode::Auto,
+        help_heading = COLORING_OPTIONS,
+        help = concat!(
+            "When to colorize the console output",
+            "\nauto disables colors when stdout is not a terminal or NO_COLOR is set,",
+            "\nand enables them when CLICOLOR_FORCE is set"
+        ),
+    )]
+    pub color: ColorMode,
+
     #[arg(
         short = 'N',
         required = false,
@@ -299,11 +559,60 @@ pub struct CliArgs {
         long = "no-color",
         default_value_t = false,
         help_heading = COLORING_OPTIONS,
-        help = "Disable message colors",
+        help = "Disable colors, same as --color never",
         action = ArgAction::SetTrue,
     )]
     pub no_color: bool,
 
+    #[arg(
+        long = "theme",
+        required = false,
+        value_name = "THEME",
+        default_value_t = BuiltinTheme::Dark,
+        help_heading = COLORING_OPTIONS,
+        help = "Color theme for levels, banners and tags",
+    )]
+    pub theme: BuiltinTheme,
+
+    #[arg(
+        long = "theme-file",
+        required = false,
+        default_value = None,
+        value_name = "FILE_PATH",
+        help_heading = COLORING_OPTIONS,
+        help = concat!(
+            "Theme file applied on top of --theme, see assets/themes/dark.txt for the keys",
+            "\nThis can be specified multiple times"
+        ),
+    )]
+    pub theme_files: Option<Vec<String>>,
+
+    #[arg(
+        long = "tag-colors",
+        required = false,
+        value_name = "MODE",
+        default_value_t = ColorAssignment::Lru,
+        help_heading = COLORING_OPTIONS,
+        help = concat!(
+            "How palette colors are assigned to tags, PIDs and packages",
+            "\nhash keeps the same color for a tag across sessions and machines"
+        ),
+    )]
+    pub tag_colors: ColorAssignment,
+
+    #[arg(
+        long = "color-depth",
+        required = false,
+        value_name = "DEPTH",
+        default_value_t = ColorDepth::Auto,
+        help_heading = COLORING_OPTIONS,
+        help = concat!(
+            "Number of colors supported by the terminal, theme colors are downgraded to it",
+            "\nDetected from COLORTERM and TERM by default"
+        ),
+    )]
+    pub color_depth: ColorDepth,
+
     #[arg(
         short = 'o',
         long = "output",
@@ -311,9 +620,147 @@ pub struct CliArgs {
         value_name = "FILE_PATH",
         default_value = None,
         help_heading = OUTPUT_OPTIONS,
-        help = format!("Save output to {}", "[FILE_PATH]".cyan().bold()),
+        help = format!(
+            "Save output to {}, compressed if it ends with .gz, .zst or .xz",
+            "[FILE_PATH]".cyan().bold()
+        ),
     )]
     pub output_path: Option<String>,
+
+    #[arg(
+        long = "raw-output",
+        required = false,
+        value_name = "FILE_PATH",
+        default_value = None,
+        help_heading = OUTPUT_OPTIONS,
+        help = concat!(
+            "Save every unfiltered logcat line to FILE_PATH",
+            "\nThe file can be piped back into pidcat later to apply different filters"
+        ),
+    )]
+    pub raw_output_path: Option<String>,
+
+    #[arg(
+        long = "record",
+        required = false,
+        value_name = "SESSION_PATH",
+        default_value = None,
+        help_heading = OUTPUT_OPTIONS,
+        help = concat!(
+            "Record every unfiltered logcat line with its arrival time and the device details",
+            "\nto SESSION_PATH, to be replayed later with --replay"
+        ),
+    )]
+    pub record_path: Option<String>,
+
+    #[arg(
+        short = 'f',
+        long = "format",
+        ignore_case = true,
+        required = false,
+        value_name = "FORMAT",
+        default_value_t = OutputFormat::Text,
+        help_heading = OUTPUT_OPTIONS,
+        help = concat!(
+            "Format of the console and --output logs",
+            "\njsonl writes one JSON object per log entry and process event",
+            "\nhtml writes a self-contained page with filters and collapsible stack traces",
+            "\nlogcat writes an Android Studio capture that can be opened in its Logcat window",
+            "\n--output paths ending with .jsonl, .html or .logcat pick their format from the extension"
+        ),
+    )]
+    pub format: OutputFormat,
+
+    #[arg(
+        long = "rotate-size",
+        required = false,
+        value_name = "SIZE",
+        default_value = None,
+        requires = "output_path",
+        value_parser = CliArgs::parse_size,
+        help_heading = OUTPUT_OPTIONS,
+        help = "Rotate the --output file once it grows past SIZE, e.g. 500K, 100M or 2G",
+    )]
+    pub rotate_size: Option<u64>,
+
+    #[arg(
+        long = "rotate-interval",
+        required = false,
+        value_name = "DURATION",
+        default_value = None,
+        requires = "output_path",
+        value_parser = CliArgs::parse_duration,
+        help_heading = OUTPUT_OPTIONS,
+        help = "Rotate the --output file every DURATION, e.g. 30m or 1h",
+    )]
+    pub rotate_interval: Option<Duration>,
+
+    #[arg(
+        long = "keep-rotated",
+        required = false,
+        value_name = "N",
+        default_value = None,
+        requires = "output_path",
+        help_heading = OUTPUT_OPTIONS,
+        help = "Keep only the newest N rotated --output files",
+    )]
+    pub keep_rotated: Option<usize>,
+
+    #[arg(
+        long = "compress-rotated",
+        required = false,
+        default_value_t = false,
+        requires = "output_path",
+        help_heading = OUTPUT_OPTIONS,
+        help = "Gzip rotated --output files in the background",
+    )]
+    pub compress_rotated: bool,
+
+    #[arg(
+        long = "until",
+        required = false,
+        value_name = "REGEX",
+        default_value = None,
+        value_parser = Regex::new,
+        help_heading = STOP_OPTIONS,
+        help = concat!(
+            "Exit with code 0 once a shown message matches REGEX",
+            "\nExits with code 1 if the log ends before a match"
+        ),
+    )]
+    pub until: Option<Regex>,
+
+    #[arg(
+        long = "duration",
+        required = false,
+        value_name = "DURATION",
+        default_value = None,
+        value_parser = CliArgs::parse_duration,
+        help_heading = STOP_OPTIONS,
+        help = "Exit with code 124 after DURATION, e.g. 90s, 5m or 1h30m",
+    )]
+    pub duration: Option<Duration>,
+
+    #[arg(
+        long = "max-lines",
+        required = false,
+        value_name = "N",
+        default_value = None,
+        help_heading = STOP_OPTIONS,
+        help = "Exit after N log lines have been shown",
+    )]
+    pub max_lines: Option<usize>,
+
+    #[arg(
+        long = "fail-on",
+        required = false,
+        value_name = "LEVEL|REGEX",
+        default_value = None,
+        value_parser = FailOn::parse,
+        help_heading = STOP_OPTIONS,
+        help = "Exit with code 3 once a shown message is at or above LEVEL, or matches REGEX",
+    )]
+    pub fail_on: Option<FailOn>,
 }
 
 impl CliArgs {
@@ -362,7 +809,198 @@ impl CliArgs {
         format!("{}\n{}\nAuthor: {}", version, description, author).leak()
     }
 
+    /// Parses durations like `500ms`, `90s`, `5m` or `1h30m`, plain numbers are seconds.
+    fn parse_duration(value: &str) -> Result<Duration, String> {
+        let err_msg = format!("Invalid duration '{value}', expected e.g. 500ms, 90s, 5m or 1h30m");
+        let value = value.trim();
+
+        if let Ok(seconds) = value.parse::<f64>() {
+            return Duration::try_from_secs_f64(seconds).map_err(|_| err_msg);
+        }
+
+        let mut duration = Duration::ZERO;
+        let mut rest = value;
+
+        while !rest.is_empty() {
+            let number_len = rest
+                .find(|char: char| !char.is_ascii_digit() && char != '.')
+                .ok_or(err_msg.clone())?;
+            let unit_len = rest[number_len..]
+                .find(|char: char| char.is_ascii_digit())
+                .unwrap_or(rest.len() - number_len);
+
+            let number = rest[..number_len]
+                .parse::<f64>()
+                .map_err(|_| err_msg.clone())?;
+            let unit_seconds = match &rest[number_len..number_len + unit_len] {
+                "ms" => 0.001,
+                "s" => 1.0,
+                "m" => 60.0,
+                "h" => 3600.0,
+                "d" => 86400.0,
+                _ => return Err(err_msg),
+            };
+
+            duration +=
+                Duration::try_from_secs_f64(number * unit_seconds).map_err(|_| err_msg.clone())?;
+            rest = &rest[number_len + unit_len..];
+        }
+
+        Ok(duration)
+    }
+
+    /// Parses `--listen` addresses, a plain port listens on every interface.
+    fn parse_listen_address(value: &str) -> Result<String, String> {
+        let value = value.trim();
+
+        if value.parse::<u16>().is_ok() {
+            Ok(format!("0.0.0.0:{value}"))
+        } else if value
+            .rsplit_once(':')
+            .is_some_and(|(_, port)| port.parse::<u16>().is_ok())
+        {
+            Ok(value.to_string())
+        } else {
+            Err(format!(
+                "Invalid address '{value}', expected e.g. 5555 or 127.0.0.1:5555"
+            ))
+        }
+    }
+
+    /// Parses playback speeds like `2x`, `0.5x` or `1.5`, `max` replays without pauses.
+    fn parse_speed(value: &str) -> Result<f64, String> {
+        let err_msg = format!("Invalid speed '{value}', expected e.g. 2x, 0.5x or max");
+        let value = value.trim().to_lowercase();
+
+        if value == "max" {
+            return Ok(f64::INFINITY);
+        }
+
+        let speed = value
+            .strip_suffix('x')
+            .unwrap_or(&value)
+            .parse::<f64>()
+            .map_err(|_| err_msg.clone())?;
+
+        if speed.is_finite() && speed > 0.0 {
+            Ok(speed)
+        } else {
+            Err(err_msg)
+        }
+    }
+
+    /// Parses sizes like `512K`, `100M` or `2G` as binary multiples, plain numbers are bytes.
+    fn parse_size(value: &str) -> Result<u64, String> {
+        let err_msg = format!("Invalid size '{value}', expected e.g. 500K, 100M or 2G");
+        let value = value.trim().to_uppercase();
+        let value = value
+            .strip_suffix("IB")
+            .or(value.strip_suffix('B'))
+            .unwrap_or(&value);
+
+        let (number, multiplier) = match value.chars().last() {
+            Some('K') => (&value[..value.len() - 1], 1u64 << 10),
+            Some('M') => (&value[..value.len() - 1], 1u64 << 20),
+            Some('G') => (&value[..value.len() - 1], 1u64 << 30),
+            _ => (value, 1),
+        };
+
+        let number = number.trim().parse::<f64>().map_err(|_| err_msg.clone())?;
+        if number <= 0.0 {
+            return Err(err_msg);
+        }
+
+        Ok((number * multiplier as f64) as u64)
+    }
+
+    /// Returns the rotation policy of the `--output` file.
+    pub fn get_rotation(&self) -> Rotation {
+        Rotation {
+            size: self.rotate_size,
+            interval: self.rotate_interval,
+            keep: self.keep_rotated,
+            compress: self.compress_rotated,
+        }
+    }
+
+    /// Returns the names shown in the source column for the `--file` paths, their file
+    /// names unless several files share the same name, or an empty list for a single file.
+    pub fn get_source_names(&self) -> Vec<String> {
+        let files = self.files.as_deref().unwrap_or_default();
+
+        if files.len() < 2 {
+            return Vec::default();
+        }
+
+        let file_names = files
+            .iter()
+            .map(|path| {
+                Path::new(path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or(path.to_string())
+            })
+            .collect::<Vec<_>>();
+
+        if file_names.iter().all_unique() {
+            file_names
+        } else {
+            files.to_vec()
+        }
+    }
+
+    /// Builds the layout matching the `--show-*` and width options, used without `--layout`.
+    fn get_default_layout(&self) -> Layout {
+        let mut template = String::default();
+
+        if !self.get_source_names().is_empty() {
+            template.push_str("{source} ");
+        }
+
+        if self.time.is_some() {
+            template.push_str("{time:>} ");
+        }
+
+        if self.show_pid {
+            template.push_str("{pid} ");
+        }
+
+        if self.show_tid || self.thread_names {
+            template.push_str("{tid} ");
+        }
+
+        if self.show_package {
+            template.push_str("{pkg} ");
+        }
+
+        if self.tag_width > 0 && (self.show_pid || self.show_package) {
+            template.push_str("{tag:>} ");
+        } else if self.tag_width > 0 {
+            template.push_str("{tag} ");
+        }
+
+        template.push_str("{level} {msg}");
+
+        Layout::parse(&template).unwrap_or_panic("Invalid default layout")
+    }
+
     pub fn parse_args() -> Self {
-        Self::parse()
+        let mut args = Self::parse();
+
+        if args.layout.is_none() {
+            args.layout = Some(args.get_default_layout());
+        }
+
+        args.color_depth = args.color_depth.resolve();
+
+        if args.no_color {
+            args.color = ColorMode::Never;
+        }
+
+        // Resolved once, --no-color then tells whether console colors are disabled
+        args.color = args.color.resolve(stdout().is_terminal());
+        args.no_color = args.color == ColorMode::Never;
+
+        args
     }
 }
@@ -0,0 +1,108 @@
+use clap::ValueEnum;
+
+use clap::builder::PossibleValue;
+
+use colored::Color;
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+/// How palette colors are assigned to tags, PIDs and packages, given with `--tag-colors`.
+#[derive(Eq, Copy, Debug, Clone, PartialEq, Default)]
+pub enum ColorAssignment {
+    /// The least recently used palette color, colors change from one session to another
+    #[default]
+    Lru,
+    /// A palette color picked from a hash of the token, the same in every session
+    Hash,
+}
+
+impl ColorAssignment {
+    /// Returns the preferred palette index of `token` in [ColorAssignment::Hash] mode.
+    ///
+    /// Uses 32-bit FNV-1a, which unlike the std hasher is stable across platforms and
+    /// Rust versions, so a tag keeps its color for everyone using the same theme.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::ColorAssignment;
+    ///
+    /// assert_eq!(ColorAssignment::get_palette_index("OkHttp", 6), 5);
+    /// assert_eq!(ColorAssignment::get_palette_index("Choreographer", 6), 2);
+    /// assert_eq!(ColorAssignment::get_palette_index("OkHttp", 0), 0);
+    /// ```
+    pub fn get_palette_index(token: &str, palette_len: usize) -> usize {
+        if palette_len == 0 {
+            return 0;
+        }
+
+        let hash = token.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+        });
+
+        hash as usize % palette_len
+    }
+
+    /// Returns the color of `token` in [ColorAssignment::Hash] mode, its hashed palette
+    /// color, or the next one not in `visible_colors` when another visible tag already
+    /// shows it.
+    ///
+    /// The color only depends on what is visible at the time, never on the tags seen before.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use colored::Color;
+    /// use pidcat::ColorAssignment;
+    ///
+    /// let palette = [Color::Red, Color::Green, Color::Yellow, Color::Blue, Color::Magenta, Color::Cyan];
+    ///
+    /// let color = ColorAssignment::get_hashed_color("OkHttp", &palette, &[]);
+    /// assert_eq!(color, Some(Color::Cyan));
+    ///
+    /// // Shifted while a tag with the same color is visible
+    /// let shifted = ColorAssignment::get_hashed_color("OkHttp", &palette, &[Color::Cyan]);
+    /// assert_eq!(shifted, Some(Color::Red));
+    ///
+    /// // Back to its own color once alone, whatever was seen before
+    /// let color = ColorAssignment::get_hashed_color("OkHttp", &palette, &[Color::Green]);
+    /// assert_eq!(color, Some(Color::Cyan));
+    /// ```
+    pub fn get_hashed_color(
+        token: &str,
+        palette: &[Color],
+        visible_colors: &[Color],
+    ) -> Option<Color> {
+        let index = Self::get_palette_index(token, palette.len());
+
+        (0..palette.len())
+            .map(|offset| palette[(index + offset) % palette.len()])
+            .find(|color| !visible_colors.contains(color))
+            .or(palette.get(index).copied())
+    }
+}
+
+impl Display for ColorAssignment {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        let name = match self {
+            Self::Lru => "lru",
+            Self::Hash => "hash",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+impl ValueEnum for ColorAssignment {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Lru, Self::Hash]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Lru => PossibleValue::new("lru"),
+            Self::Hash => PossibleValue::new("hash").alias("stable"),
+        })
+    }
+}
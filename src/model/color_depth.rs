@@ -0,0 +1,243 @@
+use clap::ValueEnum;
+
+use clap::builder::PossibleValue;
+
+use colored::Color;
+
+use std::env;
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+/// Standard xterm values of the 16 basic ANSI colors.
+pub const ANSI_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Basic ANSI colors in the order of [ANSI_PALETTE].
+const ANSI_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+    Color::BrightBlack,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightWhite,
+];
+
+/// Number of colors supported by the terminal, given with `--color-depth`.
+///
+/// Theme colors are downgraded to the nearest color the terminal can show.
+#[derive(Eq, Copy, Debug, Clone, PartialEq, Default)]
+pub enum ColorDepth {
+    /// Detected from `COLORTERM` and `TERM`
+    #[default]
+    Auto,
+    /// The 16 basic ANSI colors
+    Ansi16,
+    /// The xterm 256 color palette
+    Ansi256,
+    /// 24-bit RGB colors
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Resolves [ColorDepth::Auto] from the environment, other depths are returned as is.
+    ///
+    /// `COLORTERM=truecolor` (or `24bit`) and Windows Terminal select [ColorDepth::TrueColor],
+    /// a `TERM` containing `256color` selects [ColorDepth::Ansi256].
+    pub fn resolve(&self) -> Self {
+        if *self != Self::Auto {
+            return *self;
+        }
+
+        let color_term = env::var("COLORTERM").unwrap_or_default().to_lowercase();
+        let term = env::var("TERM").unwrap_or_default().to_lowercase();
+
+        if color_term == "truecolor" || color_term == "24bit" || env::var("WT_SESSION").is_ok() {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Returns the nearest color to `color` supported by this depth.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use colored::Color;
+    /// use pidcat::ColorDepth;
+    ///
+    /// let orange = Color::TrueColor { r: 255, g: 100, b: 0 };
+    ///
+    /// assert_eq!(ColorDepth::TrueColor.downgrade(orange), orange);
+    /// assert_eq!(ColorDepth::Ansi256.downgrade(orange), Color::AnsiColor(202));
+    /// assert_eq!(ColorDepth::Ansi16.downgrade(orange), Color::BrightRed);
+    /// assert_eq!(ColorDepth::Ansi16.downgrade(Color::AnsiColor(244)), Color::BrightBlack);
+    /// assert_eq!(ColorDepth::Ansi16.downgrade(Color::Cyan), Color::Cyan);
+    /// ```
+    pub fn downgrade(&self, color: Color) -> Color {
+        match (self, color) {
+            (Self::Auto, _) => self.resolve().downgrade(color),
+            (Self::Ansi256, Color::TrueColor { r, g, b }) => Self::get_nearest_256(r, g, b),
+            (Self::Ansi16, Color::AnsiColor(code)) if code < 16 => ANSI_COLORS[code as usize],
+            (Self::Ansi16, Color::AnsiColor(_) | Color::TrueColor { .. }) => {
+                let (r, g, b) = Self::get_rgb(color);
+
+                Self::get_nearest_16(r, g, b)
+            }
+            _ => color,
+        }
+    }
+
+    /// Returns the RGB value of a color, using the xterm values for palette colors.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use colored::Color;
+    /// use pidcat::ColorDepth;
+    ///
+    /// assert_eq!(ColorDepth::get_rgb(Color::BrightBlue), (92, 92, 255));
+    /// assert_eq!(ColorDepth::get_rgb(Color::AnsiColor(202)), (255, 95, 0));
+    /// assert_eq!(ColorDepth::get_rgb(Color::AnsiColor(244)), (128, 128, 128));
+    /// ```
+    pub fn get_rgb(color: Color) -> (u8, u8, u8) {
+        match color {
+            Color::AnsiColor(code) => Self::get_ansi_256_rgb(code),
+            Color::TrueColor { r, g, b } => (r, g, b),
+            _ => {
+                let index = ANSI_COLORS
+                    .iter()
+                    .position(|&ansi_color| ansi_color == color)
+                    .unwrap_or_default();
+
+                ANSI_PALETTE[index]
+            }
+        }
+    }
+
+    /// Returns the RGB value of an xterm 256 color code.
+    pub fn get_ansi_256_rgb(code: u8) -> (u8, u8, u8) {
+        match code {
+            0..=15 => ANSI_PALETTE[code as usize],
+            16..=231 => {
+                let index = code - 16;
+
+                (
+                    Self::get_cube_level(index / 36),
+                    Self::get_cube_level((index / 6) % 6),
+                    Self::get_cube_level(index % 6),
+                )
+            }
+            232..=255 => {
+                let gray = 8 + (code - 232) * 10;
+
+                (gray, gray, gray)
+            }
+        }
+    }
+
+    fn get_cube_level(index: u8) -> u8 {
+        if index == 0 { 0 } else { 55 + index * 40 }
+    }
+
+    fn get_cube_index(value: u8) -> u8 {
+        match value {
+            0..48 => 0,
+            48..115 => 1,
+            _ => (value - 35) / 40,
+        }
+    }
+
+    fn get_distance(first: (u8, u8, u8), second: (u8, u8, u8)) -> u32 {
+        let channel = |first: u8, second: u8| (first.abs_diff(second) as u32).pow(2);
+
+        channel(first.0, second.0) + channel(first.1, second.1) + channel(first.2, second.2)
+    }
+
+    fn get_nearest_256(r: u8, g: u8, b: u8) -> Color {
+        let (r_index, g_index, b_index) = (
+            Self::get_cube_index(r),
+            Self::get_cube_index(g),
+            Self::get_cube_index(b),
+        );
+        let cube_code = 16 + 36 * r_index + 6 * g_index + b_index;
+
+        let average = (r as u16 + g as u16 + b as u16) / 3;
+        let gray_code = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+        let cube_distance = Self::get_distance((r, g, b), Self::get_ansi_256_rgb(cube_code));
+        let gray_distance = Self::get_distance((r, g, b), Self::get_ansi_256_rgb(gray_code));
+
+        if gray_distance < cube_distance {
+            Color::AnsiColor(gray_code)
+        } else {
+            Color::AnsiColor(cube_code)
+        }
+    }
+
+    fn get_nearest_16(r: u8, g: u8, b: u8) -> Color {
+        ANSI_COLORS
+            .iter()
+            .zip(ANSI_PALETTE)
+            .min_by_key(|&(_, rgb)| Self::get_distance((r, g, b), rgb))
+            .map(|(&color, _)| color)
+            .unwrap_or(Color::White)
+    }
+}
+
+impl Display for ColorDepth {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Ansi16 => "16",
+            Self::Ansi256 => "256",
+            Self::TrueColor => "truecolor",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+impl ValueEnum for ColorDepth {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Auto, Self::Ansi16, Self::Ansi256, Self::TrueColor]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Auto => PossibleValue::new("auto"),
+            Self::Ansi16 => PossibleValue::new("16"),
+            Self::Ansi256 => PossibleValue::new("256"),
+            Self::TrueColor => PossibleValue::new("truecolor").alias("24bit"),
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use clap::ValueEnum;
+
+use clap::builder::PossibleValue;
+
+use std::env;
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+/// When the console output is colorized, given with `--color`.
+#[derive(Eq, Copy, Debug, Clone, PartialEq, Default)]
+pub enum ColorMode {
+    /// Colorize when stdout is a terminal, unless `NO_COLOR` or `CLICOLOR=0` is set,
+    /// or `CLICOLOR_FORCE` forces colors
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Resolves [ColorMode::Auto] to [ColorMode::Always] or [ColorMode::Never] from the
+    /// environment and `is_terminal`, other modes are returned as is.
+    ///
+    /// Follows <https://no-color.org> and <https://bixense.com/clicolors>, `NO_COLOR`
+    /// takes precedence over `CLICOLOR_FORCE`.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::ColorMode;
+    ///
+    /// assert_eq!(ColorMode::Never.resolve(true), ColorMode::Never);
+    /// assert_eq!(ColorMode::Always.resolve(false), ColorMode::Always);
+    /// ```
+    pub fn resolve(&self, is_terminal: bool) -> Self {
+        if *self != Self::Auto {
+            return *self;
+        }
+
+        let is_set = |name: &str| env::var(name).is_ok_and(|value| !value.is_empty());
+        let is_disabled = |name: &str| env::var(name).is_ok_and(|value| value == "0");
+
+        let is_forced = is_set("CLICOLOR_FORCE") && !is_disabled("CLICOLOR_FORCE");
+
+        if is_set("NO_COLOR") {
+            Self::Never
+        } else if is_forced || (is_terminal && !is_disabled("CLICOLOR")) {
+            Self::Always
+        } else {
+            Self::Never
+        }
+    }
+}
+
+impl Display for ColorMode {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Always => "always",
+            Self::Never => "never",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+impl ValueEnum for ColorMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Auto, Self::Always, Self::Never]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Auto => PossibleValue::new("auto"),
+            Self::Always => PossibleValue::new("always").alias("force"),
+            Self::Never => PossibleValue::new("never").alias("none"),
+        })
+    }
+}
@@ -0,0 +1,111 @@
+use flate2::read::MultiGzDecoder;
+
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Result;
+
+use xz2::read::XzDecoder;
+
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Compression of a log file or stream.
+#[derive(Eq, Copy, Debug, Clone, PartialEq, Default)]
+pub enum CompressionFormat {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl CompressionFormat {
+    /// Picks the compression from the file extension.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::CompressionFormat;
+    ///
+    /// assert_eq!(CompressionFormat::from_path("session.log.gz"), CompressionFormat::Gzip);
+    /// assert_eq!(CompressionFormat::from_path("session.log.ZST"), CompressionFormat::Zstd);
+    /// assert_eq!(CompressionFormat::from_path("session.log"), CompressionFormat::None);
+    /// ```
+    pub fn from_path(path: &str) -> Self {
+        let path = path.to_lowercase();
+
+        if path.ends_with(".gz") {
+            Self::Gzip
+        } else if path.ends_with(".zst") {
+            Self::Zstd
+        } else if path.ends_with(".xz") {
+            Self::Xz
+        } else {
+            Self::None
+        }
+    }
+
+    /// Detects the compression from the magic bytes at the start of a stream.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::CompressionFormat;
+    ///
+    /// assert_eq!(CompressionFormat::from_magic(&[0x1f, 0x8b, 0x08]), CompressionFormat::Gzip);
+    /// assert_eq!(CompressionFormat::from_magic(b"10-18 12:00"), CompressionFormat::None);
+    /// ```
+    pub fn from_magic(bytes: &[u8]) -> Self {
+        if bytes.starts_with(GZIP_MAGIC) {
+            Self::Gzip
+        } else if bytes.starts_with(ZSTD_MAGIC) {
+            Self::Zstd
+        } else if bytes.starts_with(XZ_MAGIC) {
+            Self::Xz
+        } else {
+            Self::None
+        }
+    }
+
+    /// Wraps `source` in a decoder for this compression.
+    pub fn get_decoder(&self, source: Box<dyn Read + Send>) -> Result<Box<dyn Read + Send>> {
+        Ok(match self {
+            Self::None => source,
+            Self::Gzip => Box::new(MultiGzDecoder::new(source)),
+            Self::Zstd => Box::new(ZstdDecoder::new(source)?),
+            Self::Xz => Box::new(XzDecoder::new_multi_decoder(source)),
+        })
+    }
+
+    /// Sniffs the magic bytes of `source` and transparently decompresses it, plain text
+    /// streams are passed through untouched.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use std::io::Read;
+    /// use pidcat::CompressionFormat;
+    ///
+    /// let source = Box::new("I/Tag( 1): plain".as_bytes());
+    /// let mut text = String::new();
+    /// CompressionFormat::decompress(source).unwrap().read_to_string(&mut text).unwrap();
+    /// assert_eq!(text, "I/Tag( 1): plain");
+    /// ```
+    pub fn decompress(source: Box<dyn Read + Send>) -> Result<Box<dyn Read + Send>> {
+        let mut source = source;
+        let mut magic = Vec::with_capacity(XZ_MAGIC.len());
+
+        // A pipe may deliver the magic bytes in several reads
+        (&mut source)
+            .take(XZ_MAGIC.len() as u64)
+            .read_to_end(&mut magic)?;
+
+        let compression = Self::from_magic(&magic);
+        let source = Box::new(Cursor::new(magic).chain(source));
+
+        compression.get_decoder(source)
+    }
+}
@@ -0,0 +1,68 @@
+/// Reason for PidCat to stop reading logs, mapped to the process exit code.
+///
+/// | Reason            | Exit code                                   |
+/// |-------------------|---------------------------------------------|
+/// | `StreamEnded`     | `0`, or `1` if `--until` never matched      |
+/// | `MaxLines`        | `0`, or `1` if `--until` never matched      |
+/// | `UntilMatched`    | `0`                                         |
+/// | `FailOnMatched`   | `3`                                         |
+/// | `Timeout`         | `124`                                       |
+/// | `CommandExited`   | Exit code of the command given after `--`,  |
+/// |                   | `128 + signal` if a signal killed it        |
+/// | `Interrupted`     | `0`                                         |
+#[derive(Eq, Copy, Debug, Clone, PartialEq)]
+pub enum ExitReason {
+    StreamEnded,
+    MaxLines,
+    UntilMatched,
+    FailOnMatched,
+    Timeout,
+    CommandExited(i32),
+    Interrupted,
+}
+
+impl ExitReason {
+    pub const SUCCESS: i32 = 0;
+    pub const UNTIL_NOT_MATCHED: i32 = 1;
+    pub const FAIL_ON_MATCHED: i32 = 3;
+    pub const TIMEOUT: i32 = 124;
+    pub const COMMAND_NOT_FOUND: i32 = 127;
+
+    /// Returns the exit code for this reason.
+    ///
+    /// `waiting_for_until` is `true` when an `--until` pattern was given, in which case
+    /// stopping for any reason other than a match is not a success.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::ExitReason;
+    ///
+    /// assert_eq!(ExitReason::StreamEnded.exit_code(false), 0);
+    /// assert_eq!(ExitReason::StreamEnded.exit_code(true), 1);
+    /// assert_eq!(ExitReason::Timeout.exit_code(true), 124);
+    /// ```
+    pub fn exit_code(&self, waiting_for_until: bool) -> i32 {
+        match self {
+            Self::UntilMatched => Self::SUCCESS,
+            Self::FailOnMatched => Self::FAIL_ON_MATCHED,
+            Self::Timeout => Self::TIMEOUT,
+            Self::CommandExited(exit_code) => *exit_code,
+            Self::Interrupted => Self::SUCCESS,
+            Self::StreamEnded | Self::MaxLines if waiting_for_until => Self::UNTIL_NOT_MATCHED,
+            Self::StreamEnded | Self::MaxLines => Self::SUCCESS,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::StreamEnded => "Log stream ended",
+            Self::MaxLines => "Maximum number of lines reached",
+            Self::UntilMatched => "Found --until pattern",
+            Self::FailOnMatched => "Found --fail-on condition",
+            Self::Timeout => "Duration elapsed",
+            Self::CommandExited(_) => "Command exited",
+            Self::Interrupted => "Stopped by user",
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use regex::Regex;
+
+use crate::LogLevel;
+
+/// A `--fail-on` condition, either a minimum log level or a message regex.
+#[derive(Debug, Clone)]
+pub enum FailOn {
+    Level(LogLevel),
+    Pattern(Regex),
+}
+
+impl FailOn {
+    /// Parses a `--fail-on` argument.
+    ///
+    /// Log level letters and names (`E`, `error`, `F`, `fatal`, ...) are parsed as a
+    /// [FailOn::Level], anything else as a [FailOn::Pattern].
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::FailOn;
+    /// use pidcat::LogLevel;
+    ///
+    /// assert!(matches!(FailOn::parse("fatal"), Ok(FailOn::Level(LogLevel::FATAL))));
+    /// assert!(matches!(FailOn::parse("ANR in .*"), Ok(FailOn::Pattern(_))));
+    /// ```
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let level = match value.to_lowercase().as_str() {
+            "v" | "verbose" => Some(LogLevel::VERBOSE),
+            "d" | "debug" => Some(LogLevel::DEBUG),
+            "i" | "info" => Some(LogLevel::INFO),
+            "w" | "warn" => Some(LogLevel::WARN),
+            "e" | "error" => Some(LogLevel::ERROR),
+            "f" | "fatal" => Some(LogLevel::FATAL),
+            _ => None,
+        };
+
+        match level {
+            Some(level) => Ok(Self::Level(level)),
+            None => Regex::new(value)
+                .map(Self::Pattern)
+                .map_err(|err| err.to_string()),
+        }
+    }
+
+    pub fn is_matching(&self, level: LogLevel, message: &str) -> bool {
+        match self {
+            Self::Level(min_level) => level >= *min_level,
+            Self::Pattern(regex) => regex.is_match(message),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use colored::Color;
+
+use regex::Regex;
+
+/// A `--highlight` rule, colorizing every match of `regex` inside log messages.
+///
+/// Capture groups of the regex are colorized with their own colors, picked from the
+/// highlight palette, while the rest of the match uses `color`.
+#[derive(Debug, Clone)]
+pub struct Highlight {
+    pub regex: Regex,
+    pub color: Option<Color>,
+}
+
+impl Highlight {
+    /// Parses a `REGEX[=COLOR]` highlight argument.
+    ///
+    /// The color is only split off when the text after the last `=` is a valid color name
+    /// (e.g. `red`, `bright_cyan`) or a `#RRGGBB` hex color, otherwise the whole argument
+    /// is used as the regex.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use colored::Color;
+    /// use pidcat::Highlight;
+    ///
+    /// let highlight = Highlight::parse("user=(\\d+)=bright_red").unwrap();
+    /// assert_eq!(highlight.regex.as_str(), "user=(\\d+)");
+    /// assert_eq!(highlight.color, Some(Color::BrightRed));
+    ///
+    /// let highlight = Highlight::parse("key=value").unwrap();
+    /// assert_eq!(highlight.regex.as_str(), "key=value");
+    /// assert_eq!(highlight.color, None);
+    /// ```
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (pattern, color) = match value.rsplit_once('=') {
+            Some((pattern, color_name)) if !pattern.is_empty() => {
+                match color_name.replace(['_', '-'], " ").parse::<Color>() {
+                    Ok(color) => (pattern, Some(color)),
+                    Err(_) => (value, None),
+                }
+            }
+            _ => (value, None),
+        };
+
+        let regex = Regex::new(pattern).map_err(|err| err.to_string())?;
+
+        Ok(Self { regex, color })
+    }
+}
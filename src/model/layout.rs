@@ -0,0 +1,284 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use unicode_width::UnicodeWidthStr;
+
+/// Field shown by a [LayoutColumn].
+#[derive(Eq, Copy, Debug, Clone, PartialEq)]
+pub enum LayoutField {
+    /// Name of the log file a line was read from, when several are merged
+    Source,
+    Time,
+    Pid,
+    Tid,
+    Package,
+    Tag,
+    Level,
+    Message,
+}
+
+/// Alignment of a value inside its [LayoutColumn].
+#[derive(Eq, Copy, Debug, Clone, PartialEq, Default)]
+pub enum Alignment {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+/// Where the ellipsis goes when a value is longer than its [LayoutColumn].
+#[derive(Eq, Copy, Debug, Clone, PartialEq, Default)]
+pub enum Truncation {
+    /// `com.example.verylo…`
+    #[default]
+    End,
+    /// `com.exampl…longname`
+    Middle,
+}
+
+/// A `{field:spec}` placeholder of a [Layout].
+///
+/// `width` is `None` when the template does not set one, the column then uses the
+/// matching width option, e.g. `--tag-width` for `{tag}`.
+#[derive(Eq, Debug, Clone, PartialEq)]
+pub struct LayoutColumn {
+    pub field: LayoutField,
+    pub width: Option<usize>,
+    pub alignment: Alignment,
+    pub truncation: Truncation,
+}
+
+/// Part of a [Layout], either a column or the literal text between columns.
+#[derive(Eq, Debug, Clone, PartialEq)]
+pub enum LayoutItem {
+    Column(LayoutColumn),
+    Literal(String),
+}
+
+/// Line layout given with `--layout`, e.g. `{time:12} {pkg:20<} {tid:5} {tag:>20} {level} {msg}`.
+///
+/// Each placeholder is `{field}` or `{field:spec}`, where `spec` is an optional alignment
+/// (`<` left, `>` right, `^` center) before or after an optional width, followed by an
+/// optional truncation mode (`e` ellipsis at the end, `m` ellipsis in the middle).
+///
+/// Fields are `source`, `time`, `pid`, `tid`, `pkg`, `tag`, `level` and `msg`, `{msg}` has
+/// to be the last item since long messages are wrapped below it. Use `{{` and `}}` for
+/// literal braces.
+#[derive(Eq, Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub items: Vec<LayoutItem>,
+}
+
+impl Layout {
+    /// Parses a layout template.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::Alignment;
+    /// use pidcat::Layout;
+    /// use pidcat::LayoutField;
+    /// use pidcat::LayoutItem;
+    /// use pidcat::Truncation;
+    ///
+    /// let layout = Layout::parse("{pkg:20<m} | {tag:>20} {level} {msg}").unwrap();
+    /// let LayoutItem::Column(package) = &layout.items[0] else { panic!() };
+    /// assert_eq!(package.field, LayoutField::Package);
+    /// assert_eq!(package.width, Some(20));
+    /// assert_eq!(package.alignment, Alignment::Left);
+    /// assert_eq!(package.truncation, Truncation::Middle);
+    /// assert_eq!(layout.items[1], LayoutItem::Literal(" | ".to_string()));
+    ///
+    /// assert!(Layout::parse("{msg} {tag}").is_err());
+    /// assert!(Layout::parse("{pid:abc} {msg}").is_err());
+    /// ```
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut items = Vec::default();
+        let mut literal = String::default();
+        let mut chars = template.chars().peekable();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::default();
+
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(char) => placeholder.push(char),
+                            None => return Err(format!("Unmatched '{{' in layout '{template}'")),
+                        }
+                    }
+
+                    let column = Self::parse_column(&placeholder)?;
+
+                    if !literal.is_empty() {
+                        items.push(LayoutItem::Literal(literal.clone()));
+                        literal.clear();
+                    }
+
+                    items.push(LayoutItem::Column(column));
+                }
+                '}' => return Err(format!("Unmatched '}}' in layout '{template}'")),
+                _ => literal.push(char),
+            }
+        }
+
+        let message_index = items.iter().position(|item| {
+            matches!(item, LayoutItem::Column(column) if column.field == LayoutField::Message)
+        });
+
+        match message_index {
+            Some(index) if index == items.len() - 1 && literal.is_empty() => Ok(Self { items }),
+            Some(_) => Err(format!(
+                "{{msg}} has to be the last item of layout '{template}'"
+            )),
+            None => Err(format!("Layout '{template}' is missing {{msg}}")),
+        }
+    }
+
+    fn parse_column(placeholder: &str) -> Result<LayoutColumn, String> {
+        let err_msg = format!("Invalid layout column '{{{placeholder}}}'");
+        let (name, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+
+        let field = match name.trim() {
+            "source" | "src" => LayoutField::Source,
+            "time" => LayoutField::Time,
+            "pid" => LayoutField::Pid,
+            "tid" | "thread" => LayoutField::Tid,
+            "pkg" | "package" => LayoutField::Package,
+            "tag" => LayoutField::Tag,
+            "level" | "lvl" => LayoutField::Level,
+            "msg" | "message" => LayoutField::Message,
+            _ => return Err(format!("{err_msg}, unknown field '{name}'")),
+        };
+
+        let mut spec = spec.trim();
+        let mut alignment = None;
+        let mut truncation = Truncation::End;
+
+        if let Some(rest) = spec.strip_suffix('m') {
+            truncation = Truncation::Middle;
+            spec = rest;
+        } else if let Some(rest) = spec.strip_suffix('e') {
+            spec = rest;
+        }
+
+        let mut set_alignment = |char: Option<char>| match char {
+            Some('<') => alignment = Some(Alignment::Left),
+            Some('>') => alignment = Some(Alignment::Right),
+            Some('^') => alignment = Some(Alignment::Center),
+            _ => {}
+        };
+
+        set_alignment(spec.chars().next());
+        set_alignment(spec.chars().last());
+
+        let width_str = spec.trim_matches(['<', '>', '^']);
+        let width = match width_str {
+            "" => None,
+            _ => Some(width_str.parse::<usize>().map_err(|_| err_msg.clone())?),
+        };
+
+        Ok(LayoutColumn {
+            field,
+            width,
+            alignment: alignment.unwrap_or_default(),
+            truncation,
+        })
+    }
+}
+
+impl LayoutColumn {
+    /// Fits `value` into `width` terminal cells, truncating it with `ellipsis` on grapheme
+    /// boundaries and padding it according to the column alignment.
+    ///
+    /// Wide characters, e.g. CJK and emoji, take two cells, a wide character that does not
+    /// fit entirely is replaced by padding.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::Layout;
+    /// use pidcat::LayoutItem;
+    ///
+    /// let layout = Layout::parse("{pkg:>8} {tag:6m} {msg}").unwrap();
+    /// let LayoutItem::Column(package) = &layout.items[0] else { panic!() };
+    /// let LayoutItem::Column(tag) = &layout.items[2] else { panic!() };
+    ///
+    /// assert_eq!(package.get_fitted("app", 8, "…"), "     app");
+    /// assert_eq!(package.get_fitted("com.example", 8, "…"), "com.exa…");
+    /// assert_eq!(package.get_fitted("日本語のタグ", 8, "…"), " 日本語…");
+    /// assert_eq!(tag.get_fitted("ActivityManager", 6, "…"), "Act…er");
+    /// assert_eq!(tag.get_fitted("🐛Bug👍", 5, "…"), "🐛…👍");
+    /// ```
+    pub fn get_fitted(&self, value: &str, width: usize, ellipsis: &str) -> String {
+        let ellipsis_width = ellipsis.width();
+
+        let value = if value.width() <= width {
+            value.to_string()
+        } else if width <= ellipsis_width {
+            Self::get_head(value, width)
+        } else {
+            let kept = width - ellipsis_width;
+
+            match self.truncation {
+                Truncation::End => format!("{}{ellipsis}", Self::get_head(value, kept)),
+                Truncation::Middle => {
+                    let head = Self::get_head(value, kept.div_ceil(2));
+                    let tail = Self::get_tail(value, kept - head.width());
+
+                    format!("{head}{ellipsis}{tail}")
+                }
+            }
+        };
+
+        let padding = width.saturating_sub(value.width());
+
+        match self.alignment {
+            Alignment::Left => format!("{value}{}", " ".repeat(padding)),
+            Alignment::Right => format!("{}{value}", " ".repeat(padding)),
+            Alignment::Center => format!(
+                "{}{value}{}",
+                " ".repeat(padding / 2),
+                " ".repeat(padding - padding / 2)
+            ),
+        }
+    }
+
+    /// Returns the longest start of `value` fitting in `width` cells.
+    fn get_head(value: &str, width: usize) -> String {
+        let mut head_width = 0;
+
+        value
+            .graphemes(true)
+            .take_while(|grapheme| {
+                head_width += grapheme.width();
+                head_width <= width
+            })
+            .collect()
+    }
+
+    /// Returns the longest end of `value` fitting in `width` cells.
+    fn get_tail(value: &str, width: usize) -> String {
+        let mut tail_width = 0;
+        let mut graphemes = value
+            .graphemes(true)
+            .rev()
+            .take_while(|grapheme| {
+                tail_width += grapheme.width();
+                tail_width <= width
+            })
+            .collect::<Vec<_>>();
+
+        graphemes.reverse();
+        graphemes.concat()
+    }
+}
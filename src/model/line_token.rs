@@ -0,0 +1,14 @@
+use colored::Color;
+
+/// Piece of a console line, collected until the whole line is rendered and written to
+/// every text writer at once.
+#[derive(Debug, Clone)]
+pub struct LineToken {
+    pub text: String,
+    /// Whether `text` is wrapped to the console width, e.g. the message
+    pub wrap: bool,
+    /// Width of the columns before `text`, continuation lines are indented by it
+    pub header_width: usize,
+    pub level_foreground: Color,
+    pub level_background: Color,
+}
@@ -0,0 +1,189 @@
+use chrono::Datelike;
+use chrono::Local;
+use chrono::NaiveDateTime;
+use chrono::TimeDelta;
+
+use once_cell::sync::Lazy;
+
+use regex::Regex;
+
+use crate::LogLevel;
+use crate::ValueOrPanic;
+
+/// Leap year that yearless timestamps are parsed in, so `02-29` is always a valid date.
+const LEAP_YEAR: i32 = 2000;
+
+/// Log line in `brief` format, e.g. `D/Tag( 1234): message`.
+static BRIEF_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([A-Z])/(.+?)\( *(\d+)\): (.*?)$").unwrap_or_panic("Invalid Regex for BRIEF_LINE")
+});
+
+/// Log line in `threadtime` format, optionally with the `year` and `uid` modifiers,
+/// e.g. `10-18 12:00:00.000  1234  1256 D Tag     : message`.
+static THREADTIME_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^((?:\d{4}-)?\d\d-\d\d\s+\d\d:\d\d:\d\d\.\d+)\s+(?:(\S+)\s+)?(\d+)\s+(\d+)\s+([VDIWEF])\s+(.*?)\s*: (.*?)$",
+    )
+    .unwrap_or_panic("Invalid Regex for THREADTIME_LINE")
+});
+
+/// A single parsed logcat entry.
+///
+/// Fields that are not part of the logcat format being read are `None`, e.g. `brief`
+/// lines have no timestamp, thread id or uid.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub timestamp: Option<String>,
+    /// Parsed timestamp, see [LogEntry::get_time_after] for the year of yearless ones
+    pub time: Option<NaiveDateTime>,
+    pub uid: Option<String>,
+    pub pid: String,
+    pub tid: Option<String>,
+    pub level: LogLevel,
+    pub tag: String,
+    pub message: String,
+    /// Name of the log file the entry was read from, set when several are merged
+    pub source: Option<String>,
+}
+
+impl LogEntry {
+    /// Parses a logcat line in either `threadtime` or `brief` format.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::LogEntry;
+    /// use pidcat::LogLevel;
+    ///
+    /// let entry = LogEntry::parse("10-18 12:00:00.000  1234  1256 D OkHttp  : GET /").unwrap();
+    /// assert_eq!(entry.timestamp.as_deref(), Some("10-18 12:00:00.000"));
+    /// assert_eq!(entry.tid.as_deref(), Some("1256"));
+    /// assert_eq!(entry.tag, "OkHttp");
+    ///
+    /// let entry = LogEntry::parse("W/ActivityManager(  500): Slow operation").unwrap();
+    /// assert_eq!(entry.level, LogLevel::WARN);
+    /// assert_eq!(entry.pid, "500");
+    /// assert_eq!(entry.tid, None);
+    /// ```
+    pub fn parse(line: &str) -> Option<Self> {
+        if let Some(caps) = THREADTIME_LINE.captures(line) {
+            return Some(Self {
+                timestamp: Some(caps[1].to_string()),
+                time: Self::parse_time(&caps[1], None),
+                uid: caps.get(2).map(|mat| mat.as_str().to_string()),
+                pid: caps[3].to_string(),
+                tid: Some(caps[4].to_string()),
+                level: LogLevel::from(&caps[5]),
+                tag: caps[6].trim().to_string(),
+                message: caps[7].trim().to_string(),
+                source: None,
+            });
+        }
+
+        if let Some(caps) = BRIEF_LINE.captures(line) {
+            return Some(Self {
+                timestamp: None,
+                time: None,
+                uid: None,
+                pid: caps[3].trim().to_string(),
+                tid: None,
+                level: LogLevel::from(&caps[1]),
+                tag: caps[2].trim().to_string(),
+                message: caps[4].trim().to_string(),
+                source: None,
+            });
+        }
+
+        None
+    }
+
+    /// Parses the timestamp of the entry, without the context of the lines before it.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use chrono::Timelike;
+    /// use pidcat::LogEntry;
+    ///
+    /// let entry = LogEntry::parse("2026-10-18 12:00:01.250  1234  1256 D OkHttp  : GET /").unwrap();
+    /// let time = entry.get_time().unwrap();
+    /// assert_eq!(time.second(), 1);
+    /// assert_eq!(time.nanosecond(), 250_000_000);
+    /// ```
+    pub fn get_time(&self) -> Option<NaiveDateTime> {
+        self.get_time_after(None)
+    }
+
+    /// Parses the timestamp of the entry, `previous` being the time of the line before it.
+    ///
+    /// logcat omits the year unless the `year` modifier is used, the year around `previous`
+    /// that gives the closest time is then used, so a log crossing New Year moves to the
+    /// next year while lines slightly out of order across a month end stay in the same one.
+    /// Without `previous` the current year is assumed, or the year before for dates still
+    /// to come.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use chrono::Datelike;
+    /// use pidcat::LogEntry;
+    ///
+    /// let december = LogEntry::parse("2025-12-31 23:59:59.900  1  1 I A: last").unwrap();
+    /// let january = LogEntry::parse("01-01 00:00:00.100  1  1 I A: first").unwrap();
+    /// let time = january.get_time_after(december.get_time()).unwrap();
+    /// assert_eq!((time.year(), time.month(), time.day()), (2026, 1, 1));
+    ///
+    /// // logcat interleaves its buffers, so a line may be a little older than the one before
+    /// let november = LogEntry::parse("2025-11-01 00:00:00.010  1  1 I A: later").unwrap();
+    /// let october = LogEntry::parse("10-31 23:59:59.990  1  1 I A: earlier").unwrap();
+    /// let time = october.get_time_after(november.get_time()).unwrap();
+    /// assert_eq!((time.year(), time.month(), time.day()), (2025, 10, 31));
+    ///
+    /// let leap_day = LogEntry::parse("02-29 12:00:00.000  1  1 I A: leap").unwrap();
+    /// assert_eq!(leap_day.get_time_after(december.get_time()).unwrap().year(), 2024);
+    /// ```
+    pub fn get_time_after(&self, previous: Option<NaiveDateTime>) -> Option<NaiveDateTime> {
+        Self::parse_time(self.timestamp.as_ref()?, previous)
+    }
+
+    /// Parses a logcat timestamp, with or without the year, see [LogEntry::get_time_after].
+    pub fn parse_time(timestamp: &str, previous: Option<NaiveDateTime>) -> Option<NaiveDateTime> {
+        let timestamp = timestamp.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if timestamp.matches('-').count() != 1 {
+            return NaiveDateTime::parse_from_str(&timestamp, "%Y-%m-%d %H:%M:%S%.f").ok();
+        }
+
+        let time = NaiveDateTime::parse_from_str(
+            &format!("{LEAP_YEAR}-{timestamp}"),
+            "%Y-%m-%d %H:%M:%S%.f",
+        )
+        .ok()?;
+
+        if let Some(previous) = previous {
+            let closest = (previous.year() - 1..=previous.year() + 1)
+                .filter_map(|year| time.with_year(year))
+                .min_by_key(|candidate| (*candidate - previous).abs());
+
+            return Some(closest.unwrap_or(with_year(time, previous.year())));
+        }
+
+        let now = Local::now().naive_local();
+
+        // Device clocks may run a little ahead of the local one
+        let year = if with_year(time, now.year()) > now + TimeDelta::days(1) {
+            now.year() - 1
+        } else {
+            now.year()
+        };
+
+        Some(with_year(time, year))
+    }
+}
+
+/// Moves `time` to `year`, or to the last leap year before it for `02-29`.
+fn with_year(time: NaiveDateTime, year: i32) -> NaiveDateTime {
+    (0..4)
+        .find_map(|years_back| time.with_year(year - years_back))
+        .unwrap_or(time)
+}
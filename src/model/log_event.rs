@@ -0,0 +1,18 @@
+use std::collections::HashMap;
+
+use crate::LogEntry;
+
+/// Events sent from the log parser thread, the stderr reader thread, the wrapped command,
+/// the thread name resolver and the CTRL+C handler to the main loop.
+#[derive(Debug)]
+pub enum LogEvent {
+    /// A raw logcat line, with its entry when it could be parsed
+    Line(String, Option<LogEntry>),
+    /// A line written by the log source to stderr
+    Error(String),
+    /// Thread names of a process by thread id, read from the device for `--thread-names`
+    ThreadNames(String, HashMap<String, String>),
+    EndOfStream,
+    CommandExited(i32),
+    Interrupted,
+}
@@ -2,6 +2,9 @@ use clap::ValueEnum;
 
 use clap::builder::PossibleValue;
 
+use serde::Serialize;
+use serde::Serializer;
+
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result;
@@ -51,6 +54,12 @@ impl Display for LogLevel {
     }
 }
 
+impl Serialize for LogLevel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 impl ValueEnum for LogLevel {
     fn value_variants<'a>() -> &'a [Self] {
         &[
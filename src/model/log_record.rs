@@ -0,0 +1,37 @@
+use serde::Serialize;
+
+use crate::LogLevel;
+
+/// A structured record written by [crate::OutputFormat::Jsonl] writers.
+///
+/// Serialized with a `type` field naming the variant, e.g.
+/// `{"type":"process_death","timestamp":null,"pid":"1234","package":"com.example"}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LogRecord {
+    Log {
+        timestamp: Option<String>,
+        pid: String,
+        tid: Option<String>,
+        uid: Option<String>,
+        package: Option<String>,
+        tag: String,
+        level: LogLevel,
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        source: Option<String>,
+    },
+    ProcessStart {
+        timestamp: Option<String>,
+        pid: String,
+        uid: Option<String>,
+        gids: Option<String>,
+        package: String,
+        target: Option<String>,
+    },
+    ProcessDeath {
+        timestamp: Option<String>,
+        pid: String,
+        package: String,
+    },
+}
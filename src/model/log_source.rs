@@ -4,4 +4,12 @@ use std::process::Child;
 pub enum LogSource {
     Process(Child),
     Stdin,
+    /// Log files given with `--file`
+    Files(Vec<String>),
+    /// Session file given with `--replay`
+    Replay(String),
+    /// Address given with `--listen`, logs are read from whoever connects to it
+    Listen(String),
+    /// Address given with `--connect`, logs are read from a remote host
+    Connect(String),
 }
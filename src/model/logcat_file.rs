@@ -0,0 +1,188 @@
+use chrono::DateTime;
+use chrono::Local;
+use chrono::TimeZone;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use serde_json::Value;
+
+use crate::LogEntry;
+use crate::LogLevel;
+use crate::LogRecord;
+
+/// Logcat capture saved by Android Studio, a JSON document holding the capture details
+/// and one structured entry per message.
+///
+/// Read with `--file` when the path ends with `.logcat`, and written by
+/// [crate::OutputFormat::Logcat] writers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogcatFile {
+    #[serde(default)]
+    pub metadata: LogcatMetadata,
+    #[serde(default)]
+    pub logcat_messages: Vec<LogcatMessage>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogcatMetadata {
+    /// Device the capture was made on, kept as is since PidCat does not use it
+    #[serde(default)]
+    pub device: Option<Value>,
+    #[serde(default)]
+    pub filter: String,
+    #[serde(default)]
+    pub project_application_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogcatMessage {
+    pub header: LogcatHeader,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogcatHeader {
+    /// `VERBOSE`, `DEBUG`, `INFO`, `WARN`, `ERROR` or `ASSERT`
+    pub log_level: String,
+    pub pid: u32,
+    pub tid: u32,
+    pub application_id: String,
+    pub process_name: String,
+    pub tag: String,
+    pub timestamp: LogcatTimestamp,
+}
+
+/// Time since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LogcatTimestamp {
+    pub seconds: i64,
+    pub nanos: u32,
+}
+
+impl LogcatFile {
+    /// Returns whether `path` is an Android Studio capture, from its extension.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::LogcatFile;
+    ///
+    /// assert!(LogcatFile::is_logcat_path("crash.logcat"));
+    /// assert!(!LogcatFile::is_logcat_path("crash.log"));
+    /// ```
+    pub fn is_logcat_path(path: &str) -> bool {
+        path.to_lowercase().ends_with(".logcat")
+    }
+}
+
+impl LogcatMessage {
+    /// Returns the message as logcat `threadtime` lines with the year, one per line of a
+    /// multi-line message like logcat prints them.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::LogEntry;
+    /// use pidcat::LogLevel;
+    /// use pidcat::LogcatMessage;
+    ///
+    /// let json = r#"{"header":{"logLevel":"ASSERT","pid":1234,"tid":1256,"applicationId":"com.example",
+    ///     "processName":"com.example","tag":"OkHttp","timestamp":{"seconds":1792324800,"nanos":250000000}},
+    ///     "message":"first\nsecond"}"#;
+    /// let message = serde_json::from_str::<LogcatMessage>(json).unwrap();
+    /// let lines = message.get_lines();
+    ///
+    /// assert_eq!(lines.len(), 2);
+    /// let entry = LogEntry::parse(&lines[1]).unwrap();
+    /// assert_eq!((entry.pid.as_str(), entry.tid.as_deref()), ("1234", Some("1256")));
+    /// assert_eq!(entry.level, LogLevel::FATAL);
+    /// assert_eq!(entry.message, "second");
+    /// ```
+    pub fn get_lines(&self) -> Vec<String> {
+        let header = &self.header;
+        let time = DateTime::from_timestamp(header.timestamp.seconds, header.timestamp.nanos)
+            .unwrap_or_default()
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S%.3f");
+        let level = match header.log_level.as_str() {
+            "VERBOSE" => LogLevel::VERBOSE,
+            "DEBUG" => LogLevel::DEBUG,
+            "WARN" => LogLevel::WARN,
+            "ERROR" => LogLevel::ERROR,
+            "ASSERT" => LogLevel::FATAL,
+            _ => LogLevel::INFO,
+        };
+
+        self.message
+            .split('\n')
+            .map(|line| {
+                format!(
+                    "{time} {:>5} {:>5} {level} {}: {line}",
+                    header.pid, header.tid, header.tag
+                )
+            })
+            .collect()
+    }
+
+    /// Converts a log record, process records have no Android Studio equivalent.
+    ///
+    /// Records without a timestamp, e.g. read from `brief` lines, are dated to the epoch.
+    pub fn from_record(record: &LogRecord) -> Option<Self> {
+        let LogRecord::Log {
+            timestamp,
+            pid,
+            tid,
+            package,
+            tag,
+            level,
+            message,
+            ..
+        } = record
+        else {
+            return None;
+        };
+
+        let time = timestamp
+            .as_deref()
+            .and_then(|timestamp| LogEntry::parse_time(timestamp, None))
+            .and_then(|time| Local.from_local_datetime(&time).earliest())
+            .unwrap_or_default();
+        let log_level = match level {
+            LogLevel::VERBOSE => "VERBOSE",
+            LogLevel::DEBUG => "DEBUG",
+            LogLevel::INFO => "INFO",
+            LogLevel::WARN => "WARN",
+            LogLevel::ERROR => "ERROR",
+            LogLevel::FATAL => "ASSERT",
+        };
+        // Android Studio names processes it does not know after their pid
+        let process_name = package.clone().unwrap_or(format!("pid-{pid}"));
+
+        Some(Self {
+            header: LogcatHeader {
+                log_level: log_level.to_string(),
+                pid: pid.parse().unwrap_or_default(),
+                tid: tid
+                    .as_deref()
+                    .and_then(|tid| tid.parse().ok())
+                    .unwrap_or_default(),
+                application_id: process_name
+                    .split(':')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                process_name,
+                tag: tag.clone(),
+                timestamp: LogcatTimestamp {
+                    seconds: time.timestamp(),
+                    nanos: time.timestamp_subsec_nanos(),
+                },
+            },
+            message: message.clone(),
+        })
+    }
+}
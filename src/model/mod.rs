@@ -1,10 +1,31 @@
 pub mod adb_device;
 pub mod adb_state;
 pub mod ansi_segment;
+pub mod builtin_theme;
 pub mod cli_args;
+pub mod color_assignment;
+pub mod color_depth;
+pub mod color_mode;
+pub mod compression_format;
+pub mod exit_reason;
+pub mod fail_on;
+pub mod highlight;
+pub mod layout;
+pub mod line_token;
+pub mod log_entry;
+pub mod log_event;
 pub mod log_level;
+pub mod log_record;
 pub mod log_source;
+pub mod logcat_file;
 pub mod option_unwrap;
+pub mod output_format;
+pub mod record_style;
 pub mod result_unwrap;
+pub mod rotation;
+pub mod session_record;
 pub mod state;
+pub mod system_tags_profile;
+pub mod theme;
+pub mod time_format;
 pub mod value_unwrap;
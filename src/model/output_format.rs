@@ -0,0 +1,85 @@
+use clap::ValueEnum;
+
+use clap::builder::PossibleValue;
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+use std::path::Path;
+
+use crate::CompressionFormat;
+
+/// Format used by a [crate::Writer] to write log entries.
+#[derive(Eq, Copy, Debug, Clone, PartialEq, Default)]
+pub enum OutputFormat {
+    /// Colorized columns, the same layout shown on the console
+    #[default]
+    Text,
+    /// One JSON object per log entry and process event
+    Jsonl,
+    /// Self-contained HTML page with colors, filters and collapsible stack traces
+    Html,
+    /// Android Studio Logcat capture, see [crate::LogcatFile]
+    Logcat,
+}
+
+impl OutputFormat {
+    /// Returns the format implied by the extension of an output file, if any.
+    ///
+    /// A compression extension is skipped, e.g. `session.jsonl.gz` is [OutputFormat::Jsonl].
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::OutputFormat;
+    ///
+    /// assert_eq!(OutputFormat::from_path("bug_1234.html"), Some(OutputFormat::Html));
+    /// assert_eq!(OutputFormat::from_path("session.jsonl.zst"), Some(OutputFormat::Jsonl));
+    /// assert_eq!(OutputFormat::from_path("crash.logcat"), Some(OutputFormat::Logcat));
+    /// assert_eq!(OutputFormat::from_path("session.log"), None);
+    /// ```
+    pub fn from_path(path: &str) -> Option<Self> {
+        let mut path = Path::new(path);
+
+        if CompressionFormat::from_path(&path.to_string_lossy()) != CompressionFormat::None {
+            path = Path::new(path.file_stem()?);
+        }
+
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+
+        match extension.as_str() {
+            "html" | "htm" => Some(Self::Html),
+            "jsonl" => Some(Self::Jsonl),
+            "logcat" => Some(Self::Logcat),
+            _ => None,
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        let name = match self {
+            Self::Text => "text",
+            Self::Jsonl => "jsonl",
+            Self::Html => "html",
+            Self::Logcat => "logcat",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+impl ValueEnum for OutputFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Text, Self::Jsonl, Self::Html, Self::Logcat]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Text => PossibleValue::new("text"),
+            Self::Jsonl => PossibleValue::new("jsonl").alias("json"),
+            Self::Html => PossibleValue::new("html"),
+            Self::Logcat => PossibleValue::new("logcat").alias("studio"),
+        })
+    }
+}
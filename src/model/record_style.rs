@@ -0,0 +1,15 @@
+use colored::Color;
+
+/// Console colors of a [crate::LogRecord], used by writers that keep colors without ANSI
+/// codes, like [crate::OutputFormat::Html].
+#[derive(Debug, Clone)]
+pub struct RecordStyle {
+    /// Color of the tag, or of the package for process records
+    pub token_color: Color,
+    /// Foreground of the level badge, or of the banner for process records
+    pub level_foreground: Color,
+    /// Background of the level badge, or of the banner for process records
+    pub level_background: Color,
+    /// Message with the ANSI codes added by `--highlight` and the built-in message rules
+    pub message: String,
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+/// Rotation policy of the `--output` file.
+///
+/// The file is rolled once it grows past `size` bytes or has been open for `interval`,
+/// whichever comes first, and only the newest `keep` rotated segments are kept.
+#[derive(Debug, Clone, Default)]
+pub struct Rotation {
+    pub size: Option<u64>,
+    pub interval: Option<Duration>,
+    pub keep: Option<usize>,
+    pub compress: bool,
+}
+
+impl Rotation {
+    /// Returns whether any rotation trigger is set.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use pidcat::Rotation;
+    ///
+    /// assert!(!Rotation::default().is_enabled());
+    ///
+    /// let rotation = Rotation {
+    ///     interval: Some(Duration::from_secs(3600)),
+    ///     ..Rotation::default()
+    /// };
+    /// assert!(rotation.is_enabled());
+    /// ```
+    pub fn is_enabled(&self) -> bool {
+        self.size.is_some() || self.interval.is_some()
+    }
+}
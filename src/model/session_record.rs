@@ -0,0 +1,42 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Version of the session file format, written to [SessionRecord::Metadata].
+pub const SESSION_VERSION: u32 = 1;
+
+/// A line of a session file written with `--record` and read back with `--replay`.
+///
+/// Session files are JSONL, a [SessionRecord::Metadata] record followed by one
+/// [SessionRecord::Line] per raw logcat line, e.g.
+/// `{"type":"line","elapsed_ms":1250,"line":"10-18 12:00:00.000  1234  1234 I Tag: message"}`.
+///
+/// ### Example
+///
+/// ```
+/// use pidcat::SessionRecord;
+///
+/// let json = r#"{"type":"line","elapsed_ms":1250,"line":"I/Tag( 1): message"}"#;
+/// let record = serde_json::from_str::<SessionRecord>(json).unwrap();
+///
+/// assert_eq!(record, SessionRecord::Line { elapsed_ms: 1250, line: "I/Tag( 1): message".to_string() });
+/// assert_eq!(serde_json::to_string(&record).unwrap(), json);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionRecord {
+    Metadata {
+        version: u32,
+        /// RFC 3339 time the recording started at
+        recorded_at: String,
+        pidcat_version: String,
+        serial: Option<String>,
+        manufacturer: Option<String>,
+        model: Option<String>,
+        android_version: Option<String>,
+    },
+    Line {
+        /// Milliseconds between the start of the recording and the arrival of the line
+        elapsed_ms: u64,
+        line: String,
+    },
+}
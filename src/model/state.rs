@@ -1,15 +1,44 @@
+use chrono::NaiveDateTime;
+
 use std::collections::HashMap;
+use std::collections::HashSet;
+
+use std::sync::mpsc::Sender;
+
+use std::time::Instant;
 
+use crate::ExitReason;
 use crate::LogLevel;
+use crate::Theme;
 
 #[derive(Debug)]
 pub struct State {
     pub pids_map: HashMap<String, String>,
+    pub uids_map: HashMap<String, String>,
     pub last_tag: Option<String>,
     pub app_pid: Option<String>,
     pub log_level: LogLevel,
     pub named_processes: Vec<String>,
     pub catchall_package: Vec<String>,
+    pub ignored_packages: Vec<String>,
     pub token_colors: Vec<colored::Color>,
     pub known_tokens: HashMap<String, colored::Color>,
+    /// Most recently shown tokens, oldest first, capped to the palette size
+    pub visible_tokens: Vec<String>,
+    /// Most recently shown tags with the color they were shown in, for `--tag-colors hash`
+    pub visible_tags: Vec<(String, colored::Color)>,
+    pub shown_lines: usize,
+    pub exit_reason: Option<ExitReason>,
+    pub first_time: Option<NaiveDateTime>,
+    pub last_shown_time: Option<NaiveDateTime>,
+    pub start_times: HashMap<String, NaiveDateTime>,
+    /// Thread names by pid then tid
+    pub thread_names: HashMap<String, HashMap<String, String>>,
+    /// Thread ids shown without a name by pid, until a later lookup finds them
+    pub missing_threads: HashMap<String, HashSet<String>>,
+    /// Time of the last thread name lookup of each pid, to limit how often ps runs
+    pub thread_lookup_times: HashMap<String, Instant>,
+    /// Queries the thread names of a pid off the render loop, only set for live devices
+    pub thread_name_requests: Option<Sender<String>>,
+    pub theme: Theme,
 }
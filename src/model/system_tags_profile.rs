@@ -0,0 +1,130 @@
+use clap::ValueEnum;
+
+use clap::builder::PossibleValue;
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+static AOSP_TAGS: &str = include_str!("../../assets/system_tags/aosp.txt");
+static GOOGLE_TAGS: &str = include_str!("../../assets/system_tags/google.txt");
+static SAMSUNG_TAGS: &str = include_str!("../../assets/system_tags/samsung.txt");
+static XIAOMI_TAGS: &str = include_str!("../../assets/system_tags/xiaomi.txt");
+static OPLUS_TAGS: &str = include_str!("../../assets/system_tags/oplus.txt");
+
+/// Vendor specific list of noisy system tags used by `--ignore-system-tags`.
+///
+/// Every profile includes the [SystemTagsProfile::Aosp] tags, vendor profiles only add
+/// the tags emitted by their own framework additions.
+#[derive(Eq, Copy, Debug, Clone, PartialEq, Default)]
+pub enum SystemTagsProfile {
+    #[default]
+    Auto,
+    Aosp,
+    Google,
+    Samsung,
+    Xiaomi,
+    Oplus,
+}
+
+impl SystemTagsProfile {
+    /// Maps the value of `ro.product.manufacturer` to a vendor profile.
+    ///
+    /// Unknown manufacturers fall back to [SystemTagsProfile::Aosp].
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::SystemTagsProfile;
+    ///
+    /// assert_eq!(SystemTagsProfile::from_manufacturer("OnePlus"), SystemTagsProfile::Oplus);
+    /// assert_eq!(SystemTagsProfile::from_manufacturer("Fairphone"), SystemTagsProfile::Aosp);
+    /// ```
+    pub fn from_manufacturer(manufacturer: &str) -> Self {
+        match manufacturer.trim().to_lowercase().as_str() {
+            "google" => Self::Google,
+            "samsung" => Self::Samsung,
+            "xiaomi" | "redmi" | "poco" => Self::Xiaomi,
+            "oneplus" | "oppo" | "realme" => Self::Oplus,
+            _ => Self::Aosp,
+        }
+    }
+
+    /// Returns the tag regexes of this profile, including the AOSP base list.
+    ///
+    /// [SystemTagsProfile::Auto] has to be resolved with [SystemTagsProfile::from_manufacturer]
+    /// first, on its own it only yields the AOSP base list.
+    pub fn tags(&self) -> Vec<String> {
+        let vendor_tags = match self {
+            Self::Auto | Self::Aosp => "",
+            Self::Google => GOOGLE_TAGS,
+            Self::Samsung => SAMSUNG_TAGS,
+            Self::Xiaomi => XIAOMI_TAGS,
+            Self::Oplus => OPLUS_TAGS,
+        };
+
+        Self::parse_tags(AOSP_TAGS)
+            .into_iter()
+            .chain(Self::parse_tags(vendor_tags))
+            .collect()
+    }
+
+    /// Parses a system tags file, one regex per line.
+    ///
+    /// Empty lines and lines starting with `#` are skipped.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use pidcat::SystemTagsProfile;
+    ///
+    /// let tags = SystemTagsProfile::parse_tags("# comment\nHWUI\n\n  skia  \n");
+    /// assert_eq!(tags, vec!["HWUI", "skia"]);
+    /// ```
+    pub fn parse_tags(content: &str) -> Vec<String> {
+        content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.to_string())
+            .collect()
+    }
+}
+
+impl Display for SystemTagsProfile {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Aosp => "aosp",
+            Self::Google => "google",
+            Self::Samsung => "samsung",
+            Self::Xiaomi => "xiaomi",
+            Self::Oplus => "oplus",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+impl ValueEnum for SystemTagsProfile {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::Auto,
+            Self::Aosp,
+            Self::Google,
+            Self::Samsung,
+            Self::Xiaomi,
+            Self::Oplus,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Auto => PossibleValue::new("auto"),
+            Self::Aosp => PossibleValue::new("aosp"),
+            Self::Google => PossibleValue::new("google").alias("pixel"),
+            Self::Samsung => PossibleValue::new("samsung"),
+            Self::Xiaomi => PossibleValue::new("xiaomi").alias("miui"),
+            Self::Oplus => PossibleValue::new("oplus").aliases(["oneplus", "oppo", "realme"]),
+        })
+    }
+}
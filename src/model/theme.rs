@@ -0,0 +1,203 @@
+use colored::Color;
+
+use std::collections::HashMap;
+
+use crate::BuiltinTheme;
+use crate::ColorDepth;
+use crate::LogLevel;
+
+/// Console colors used for levels, banners, the time column and tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub level_foreground: Color,
+    pub level_verbose: Color,
+    pub level_debug: Color,
+    pub level_info: Color,
+    pub level_warn: Color,
+    pub level_error: Color,
+    pub level_fatal: Color,
+    pub banner_start: Color,
+    pub banner_death: Color,
+    pub banner_text: Color,
+    pub time: Color,
+    /// Colors assigned to tags, PIDs and packages
+    pub palette: Vec<Color>,
+    /// Fixed colors of known tags, bypassing the palette
+    pub tags: HashMap<String, Color>,
+}
+
+impl Theme {
+    /// Loads a built-in theme, then applies each theme file on top of it.
+    pub fn load(builtin: BuiltinTheme, files: &[String]) -> Result<Self, String> {
+        let mut theme = Self::parse(builtin.content())?;
+
+        for file in files {
+            let content = std::fs::read_to_string(file)
+                .map_err(|err| format!("Failed to read theme file '{file}': {err}"))?;
+
+            theme
+                .apply(&content)
+                .map_err(|err| format!("Invalid theme file '{file}': {err}"))?;
+        }
+
+        Ok(theme)
+    }
+
+    /// Parses a theme file, keys missing from it are white.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use colored::Color;
+    /// use pidcat::Theme;
+    ///
+    /// let theme = Theme::parse("level.error = #ff6400\npalette = red, bright blue\ntag.OkHttp = cyan").unwrap();
+    /// assert_eq!(theme.level_error, Color::TrueColor { r: 255, g: 100, b: 0 });
+    /// assert_eq!(theme.palette, vec![Color::Red, Color::BrightBlue]);
+    /// assert_eq!(theme.tags["OkHttp"], Color::Cyan);
+    ///
+    /// assert!(Theme::parse("level.error = orange-ish").is_err());
+    /// assert!(Theme::parse("banner.unknown = red").is_err());
+    /// ```
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut theme = Self {
+            level_foreground: Color::White,
+            level_verbose: Color::White,
+            level_debug: Color::White,
+            level_info: Color::White,
+            level_warn: Color::White,
+            level_error: Color::White,
+            level_fatal: Color::White,
+            banner_start: Color::White,
+            banner_death: Color::White,
+            banner_text: Color::White,
+            time: Color::White,
+            palette: Vec::default(),
+            tags: HashMap::default(),
+        };
+
+        theme.apply(content)?;
+
+        Ok(theme)
+    }
+
+    /// Overrides the colors of this theme with the keys set in a theme file.
+    ///
+    /// Empty lines and lines starting with `#` are skipped.
+    pub fn apply(&mut self, content: &str) -> Result<(), String> {
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let err_msg = format!("Line {}: '{line}'", index + 1);
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("{err_msg}, expected 'key = value'"))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            if key == "palette" {
+                self.palette = value
+                    .split(',')
+                    .map(|color| Self::parse_color(color, &err_msg))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                continue;
+            }
+
+            let color = Self::parse_color(value, &err_msg)?;
+
+            if let Some(tag) = key.strip_prefix("tag.") {
+                self.tags.insert(tag.to_string(), color);
+
+                continue;
+            }
+
+            let field = match key {
+                "level.foreground" => &mut self.level_foreground,
+                "level.verbose" => &mut self.level_verbose,
+                "level.debug" => &mut self.level_debug,
+                "level.info" => &mut self.level_info,
+                "level.warn" => &mut self.level_warn,
+                "level.error" => &mut self.level_error,
+                "level.fatal" => &mut self.level_fatal,
+                "banner.start" => &mut self.banner_start,
+                "banner.death" => &mut self.banner_death,
+                "banner.text" => &mut self.banner_text,
+                "time" => &mut self.time,
+                _ => return Err(format!("{err_msg}, unknown key '{key}'")),
+            };
+
+            *field = color;
+        }
+
+        Ok(())
+    }
+
+    fn parse_color(value: &str, err_msg: &str) -> Result<Color, String> {
+        let value = value.trim();
+
+        value
+            .replace(['_', '-'], " ")
+            .parse::<Color>()
+            .map_err(|_| format!("{err_msg}, invalid color '{value}'"))
+    }
+
+    /// Returns the foreground and background of the badge of a level.
+    pub fn get_level_colors(&self, level: LogLevel) -> (Color, Color) {
+        let background = match level {
+            LogLevel::VERBOSE => self.level_verbose,
+            LogLevel::DEBUG => self.level_debug,
+            LogLevel::INFO => self.level_info,
+            LogLevel::WARN => self.level_warn,
+            LogLevel::ERROR => self.level_error,
+            LogLevel::FATAL => self.level_fatal,
+        };
+
+        (self.level_foreground, background)
+    }
+
+    /// Returns a copy of this theme with every color downgraded to `depth`.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use colored::Color;
+    /// use pidcat::ColorDepth;
+    /// use pidcat::Theme;
+    ///
+    /// let theme = Theme::parse("level.error = #ff6400\ntag.DEBUG = yellow").unwrap();
+    /// let theme = theme.downgrade(ColorDepth::Ansi16);
+    /// assert_eq!(theme.level_error, Color::BrightRed);
+    /// assert_eq!(theme.tags["DEBUG"], Color::Yellow);
+    /// ```
+    pub fn downgrade(&self, depth: ColorDepth) -> Self {
+        let depth = depth.resolve();
+
+        Self {
+            level_foreground: depth.downgrade(self.level_foreground),
+            level_verbose: depth.downgrade(self.level_verbose),
+            level_debug: depth.downgrade(self.level_debug),
+            level_info: depth.downgrade(self.level_info),
+            level_warn: depth.downgrade(self.level_warn),
+            level_error: depth.downgrade(self.level_error),
+            level_fatal: depth.downgrade(self.level_fatal),
+            banner_start: depth.downgrade(self.banner_start),
+            banner_death: depth.downgrade(self.banner_death),
+            banner_text: depth.downgrade(self.banner_text),
+            time: depth.downgrade(self.time),
+            palette: self
+                .palette
+                .iter()
+                .map(|&color| depth.downgrade(color))
+                .collect(),
+            tags: self
+                .tags
+                .iter()
+                .map(|(tag, &color)| (tag.clone(), depth.downgrade(color)))
+                .collect(),
+        }
+    }
+}
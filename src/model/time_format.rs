@@ -0,0 +1,100 @@
+use chrono::NaiveDateTime;
+
+use clap::ValueEnum;
+
+use clap::builder::PossibleValue;
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+/// Mode of the `--time` column.
+#[derive(Eq, Copy, Debug, Clone, PartialEq)]
+pub enum TimeFormat {
+    /// Device time of the log entry, e.g. `10-18 12:00:00.150`
+    Absolute,
+    /// Time since the target process started, or since the first log entry
+    Relative,
+    /// Time since the previous shown log entry
+    Delta,
+}
+
+impl TimeFormat {
+    /// Returns the width of the time column.
+    pub fn width(&self) -> usize {
+        match self {
+            Self::Absolute => 18,
+            Self::Relative => 13,
+            Self::Delta => 10,
+        }
+    }
+
+    /// Formats `time` for the time column, `reference` is the start time for
+    /// [TimeFormat::Relative] and the previous entry time for [TimeFormat::Delta].
+    ///
+    /// The text is not padded, and is empty when there is no `reference` to compare with.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pidcat::TimeFormat;
+    ///
+    /// let start = NaiveDateTime::parse_from_str("2026-10-18 12:00:00.000", "%Y-%m-%d %H:%M:%S%.f").unwrap();
+    /// let time = NaiveDateTime::parse_from_str("2026-10-18 13:02:03.450", "%Y-%m-%d %H:%M:%S%.f").unwrap();
+    ///
+    /// assert_eq!(TimeFormat::Absolute.get_display(time, None), "10-18 13:02:03.450");
+    /// assert_eq!(TimeFormat::Relative.get_display(time, Some(start)), "+01:02:03.450");
+    /// assert_eq!(TimeFormat::Delta.get_display(time, Some(start)), "+3723.450");
+    /// assert_eq!(TimeFormat::Delta.get_display(time, None), "");
+    /// ```
+    pub fn get_display(&self, time: NaiveDateTime, reference: Option<NaiveDateTime>) -> String {
+        if *self == Self::Absolute {
+            return time.format("%m-%d %H:%M:%S%.3f").to_string();
+        }
+
+        let Some(reference) = reference else {
+            return String::new();
+        };
+
+        let millis = (time - reference).num_milliseconds();
+        let sign = if millis < 0 { "-" } else { "+" };
+        let millis = millis.unsigned_abs();
+
+        match self {
+            Self::Relative => format!(
+                "{sign}{:02}:{:02}:{:02}.{:03}",
+                millis / 3_600_000,
+                millis / 60_000 % 60,
+                millis / 1000 % 60,
+                millis % 1000
+            ),
+            _ => format!("{sign}{}.{:03}", millis / 1000, millis % 1000),
+        }
+    }
+}
+
+impl Display for TimeFormat {
+    fn fmt(&self, formatter: &mut Formatter) -> Result {
+        let name = match self {
+            Self::Absolute => "absolute",
+            Self::Relative => "relative",
+            Self::Delta => "delta",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+impl ValueEnum for TimeFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Absolute, Self::Relative, Self::Delta]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Absolute => PossibleValue::new("absolute").alias("abs"),
+            Self::Relative => PossibleValue::new("relative").alias("rel"),
+            Self::Delta => PossibleValue::new("delta"),
+        })
+    }
+}